@@ -10,7 +10,7 @@
 ///
 /// Run with: cargo run --example different_wallet
 
-use bonk_staking_rewards::{BonkStakingClient, DURATION_6_MONTHS};
+use bonk_staking_rewards::{BonkStakingClient, LockDuration};
 use solana_sdk::signature::{read_keypair_file, Keypair, Signer};
 use std::path::Path;
 
@@ -48,7 +48,7 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
     let client = BonkStakingClient::new(RPC_URL.to_string());
 
     // Check balance of the test wallet
-    let bonk_balance = client.get_bonk_balance(&test_wallet.pubkey())?;
+    let bonk_balance = client.get_token_balance(&test_wallet.pubkey())?;
     println!("BONK Balance: {:.2} BONK", bonk_balance as f64 / 100_000.0);
     
     if bonk_balance == 0 {
@@ -61,10 +61,10 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
         println!("\nThis wallet has BONK! You can stake:");
         let amount = 10_000_000u64; // 100 BONK
         println!("  Amount: {:.2} BONK", amount as f64 / 100_000.0);
-        println!("  Duration: {} days", DURATION_6_MONTHS);
+        println!("  Duration: {} days", LockDuration::SIX_MONTHS.as_days());
         
         // Uncomment to actually stake:
-        // let sig = client.stake(&test_wallet, amount, DURATION_6_MONTHS, None)?;
+        // let sig = client.stake(&test_wallet, amount, LockDuration::SIX_MONTHS, None)?;
         // println!("  Transaction: {}", sig);
     }
 
@@ -80,7 +80,7 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
             // let env_wallet = read_keypair_file(Path::new(&path))?;
             // println!("Loaded wallet: {}", env_wallet.pubkey());
             
-            // let balance = client.get_bonk_balance(&env_wallet.pubkey())?;
+            // let balance = client.get_token_balance(&env_wallet.pubkey())?;
             // println!("Balance: {:.2} BONK", balance as f64 / 100_000.0);
         }
         Err(_) => {
@@ -102,7 +102,7 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
     for (i, path) in wallet_paths.iter().enumerate() {
         match read_keypair_file(Path::new(path)) {
             Ok(wallet) => {
-                let balance = client.get_bonk_balance(&wallet.pubkey())?;
+                let balance = client.get_token_balance(&wallet.pubkey())?;
                 let stake_balance = client.get_stake_balance(&wallet.pubkey())?;
                 
                 println!("\nWallet {}: {}", i + 1, wallet.pubkey());
@@ -125,14 +125,14 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
     let default_wallet_path = "/Users/hogyzen12/.config/solana/6tBou5MHL5aWpDy6cgf3wiwGGK2mR8qs68ujtpaoWrf2.json";
     match read_keypair_file(Path::new(default_wallet_path)) {
         Ok(wallet) => {
-            let balance = client.get_bonk_balance(&wallet.pubkey())?;
+            let balance = client.get_token_balance(&wallet.pubkey())?;
             println!("Wallet: {}", wallet.pubkey());
             println!("Balance: {:.2} BONK", balance as f64 / 100_000.0);
             
             if balance >= 10_000_000 {
                 println!("\nStaking 100 BONK for 180 days...");
                 let amount = 10_000_000u64; // 100 BONK
-                let sig = client.stake(&wallet, amount, DURATION_6_MONTHS, None)?;
+                let sig = client.stake(&wallet, amount, LockDuration::SIX_MONTHS, None)?;
                 println!("Transaction: {}", sig);
                 println!("Success!");
             } else {
@@ -11,13 +11,7 @@
 ///
 /// Run with: cargo run --example stake
 
-use bonk_staking_rewards::{
-    BonkStakingClient, 
-    DURATION_1_MONTH,
-    DURATION_3_MONTHS,
-    DURATION_6_MONTHS,
-    DURATION_12_MONTHS,
-};
+use bonk_staking_rewards::{BonkStakingClient, LockDuration};
 use solana_sdk::signature::{read_keypair_file, Signer};
 use std::path::Path;
 
@@ -40,7 +34,7 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
     println!("Current Balances:");
     println!("-----------------");
     
-    let bonk_balance = client.get_bonk_balance(&user.pubkey())?;
+    let bonk_balance = client.get_token_balance(&user.pubkey())?;
     let bonk_ui_balance = bonk_balance as f64 / 100_000.0; // BONK has 5 decimals
     println!("BONK Balance: {:.2} BONK ({} lamports)", bonk_ui_balance, bonk_balance);
     
@@ -70,9 +64,9 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
     println!("1. Stake 100 BONK for 30 days (1 month)");
     let amount_1 = 10_000_000u64; // 100 BONK
     println!("   Amount: {:.2} BONK", amount_1 as f64 / 100_000.0);
-    println!("   Duration: {} days", DURATION_1_MONTH);
+    println!("   Duration: {} days", LockDuration::ONE_MONTH.as_days());
     println!("   Executing stake...");
-    let sig = client.stake(&user, amount_1, DURATION_1_MONTH, None)?;
+    let sig = client.stake(&user, amount_1, LockDuration::ONE_MONTH, None)?;
     println!("   Transaction: {}", sig);
     println!("   Success!");
     println!();
@@ -81,10 +75,10 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
     println!("2. Stake 500 BONK for 90 days (3 months)");
     let amount_2 = 50_000_000u64; // 500 BONK
     println!("   Amount: {:.2} BONK", amount_2 as f64 / 100_000.0);
-    println!("   Duration: {} days", DURATION_3_MONTHS);
+    println!("   Duration: {} days", LockDuration::THREE_MONTHS.as_days());
     println!("   (Uncomment to execute)");
     // Uncomment to actually stake:
-    // let sig = client.stake(&user, amount_2, DURATION_3_MONTHS, None)?;
+    // let sig = client.stake(&user, amount_2, LockDuration::THREE_MONTHS, None)?;
     // println!("   Transaction: {}\n", sig);
     println!();
 
@@ -92,10 +86,10 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
     println!("3. Stake 1000 BONK for 180 days (6 months)");
     let amount_3 = 100_000_000u64; // 1000 BONK
     println!("   Amount: {:.2} BONK", amount_3 as f64 / 100_000.0);
-    println!("   Duration: {} days", DURATION_6_MONTHS);
+    println!("   Duration: {} days", LockDuration::SIX_MONTHS.as_days());
     println!("   (Uncomment to execute)");
     // Uncomment to actually stake:
-    // let sig = client.stake(&user, amount_3, DURATION_6_MONTHS, None)?;
+    // let sig = client.stake(&user, amount_3, LockDuration::SIX_MONTHS, None)?;
     // println!("   Transaction: {}\n", sig);
     println!();
 
@@ -103,11 +97,11 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
     println!("4. Stake 2000 BONK for 365 days (12 months) with nonce 5");
     let amount_4 = 200_000_000u64; // 2000 BONK
     println!("   Amount: {:.2} BONK", amount_4 as f64 / 100_000.0);
-    println!("   Duration: {} days", DURATION_12_MONTHS);
+    println!("   Duration: {} days", LockDuration::TWELVE_MONTHS.as_days());
     println!("   Nonce: 5 (manually specified)");
     println!("   (Uncomment to execute)");
     // Uncomment to actually stake:
-    // let sig = client.stake(&user, amount_4, DURATION_12_MONTHS, Some(5))?;
+    // let sig = client.stake(&user, amount_4, LockDuration::TWELVE_MONTHS, Some(5))?;
     // println!("   Transaction: {}\n", sig);
     println!();
 
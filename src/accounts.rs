@@ -1,20 +1,175 @@
 //! Account types and utilities for BONK staking
 
+use borsh::{BorshDeserialize, BorshSerialize};
 use solana_sdk::pubkey::Pubkey;
 use spl_associated_token_account::get_associated_token_address;
 
-use crate::{BONK_MINT, BONK_STAKE_MINT};
+use crate::{
+    error::{BonkStakingError, Result},
+    pool::PoolConfig,
+    BONK_MINT, BONK_STAKE_MINT,
+};
+
+/// Maximum number of reward pools a stake pool can have
+pub const MAX_REWARD_POOLS: usize = 10;
+
+/// Anchor discriminator for StakePool accounts
+pub const STAKE_POOL_DISCRIMINATOR: [u8; 8] = [121, 34, 206, 21, 79, 127, 255, 28];
+
+/// Anchor discriminator for StakeDepositReceipt accounts
+pub const STAKE_DEPOSIT_RECEIPT_DISCRIMINATOR: [u8; 8] = [210, 98, 254, 196, 151, 68, 235, 0];
 
 /// Get the user's BONK token account (ATA)
+#[deprecated(note = "BONK pool only, use `get_user_pool_token_ata`")]
 pub fn get_user_bonk_ata(user: &Pubkey) -> Pubkey {
     get_associated_token_address(user, &BONK_MINT)
 }
 
-/// Get the user's stake token account (ATA) for the stake mint
+/// Get the user's stake token account (ATA) for the BONK stake mint
+#[deprecated(note = "BONK pool only, use `get_user_pool_stake_ata`")]
 pub fn get_user_stake_ata(user: &Pubkey) -> Pubkey {
     get_associated_token_address(user, &BONK_STAKE_MINT)
 }
 
+/// Get the user's token account (ATA) for a pool's mint
+pub fn get_user_pool_token_ata(user: &Pubkey, pool: &PoolConfig) -> Pubkey {
+    get_associated_token_address(user, &pool.mint)
+}
+
+/// Get the user's stake token account (ATA) for a pool's stake mint
+pub fn get_user_pool_stake_ata(user: &Pubkey, pool: &PoolConfig) -> Pubkey {
+    get_associated_token_address(user, &pool.stake_mint)
+}
+
+/// A reward pool slot inside a stake pool
+#[derive(BorshSerialize, BorshDeserialize, Debug, Clone, Default, PartialEq, Eq)]
+pub struct RewardPool {
+    /// Token account holding this pool's rewards (default pubkey when unused)
+    pub reward_vault: Pubkey,
+    /// Accumulated rewards per unit of effective stake
+    pub rewards_per_effective_stake: u128,
+    /// Reward vault balance at the last update
+    pub last_amount: u64,
+    _padding0: [u8; 8],
+}
+
+impl RewardPool {
+    /// Check if this reward pool slot is in use
+    pub fn is_initialized(&self) -> bool {
+        self.reward_vault != Pubkey::default()
+    }
+}
+
+/// On-chain stake pool
+///
+/// Layout matches the spl-token-staking `StakePool` account (after the 8-byte
+/// Anchor discriminator). Trailing padding and reserved bytes are not decoded.
+#[derive(BorshSerialize, BorshDeserialize, Debug, Clone, PartialEq, Eq)]
+pub struct StakePool {
+    /// Authority allowed to manage the pool
+    pub authority: Pubkey,
+    /// Sum of the effective stake of all deposits
+    pub total_weighted_stake: u128,
+    /// Token account holding the staked tokens
+    pub vault: Pubkey,
+    /// Mint of the token being staked
+    pub mint: Pubkey,
+    /// Mint of the stake token minted to depositors
+    pub stake_mint: Pubkey,
+    /// Reward pool slots, unused slots have a default reward vault
+    pub reward_pools: [RewardPool; MAX_REWARD_POOLS],
+    /// Weight applied to deposits locked for `min_duration`
+    pub base_weight: u64,
+    /// Weight applied to deposits locked for `max_duration`
+    pub max_weight: u64,
+    /// Minimum lockup duration in seconds
+    pub min_duration: u64,
+    /// Maximum lockup duration in seconds
+    pub max_duration: u64,
+    /// Nonce used to derive the pool address
+    pub nonce: u8,
+    /// Bump seed of the pool address
+    pub bump_seed: u8,
+}
+
+impl StakePool {
+    /// Decode a stake pool from raw account data
+    ///
+    /// Checks the 8-byte Anchor discriminator before deserializing.
+    pub fn try_deserialize(data: &[u8]) -> Result<Self> {
+        if data.len() < 8 || data[..8] != STAKE_POOL_DISCRIMINATOR {
+            return Err(BonkStakingError::InvalidAccountData(
+                "Not a StakePool account".to_string(),
+            ));
+        }
+
+        let mut body = &data[8..];
+        Self::deserialize(&mut body).map_err(|_| BonkStakingError::DeserializationError)
+    }
+
+    /// Iterate over the initialized reward pools with their slot index
+    pub fn initialized_reward_pools(&self) -> impl Iterator<Item = (usize, &RewardPool)> {
+        self.reward_pools
+            .iter()
+            .enumerate()
+            .filter(|(_, pool)| pool.is_initialized())
+    }
+}
+
+/// On-chain stake deposit receipt, one per stake position
+///
+/// Layout matches the spl-token-staking `StakeDepositReceipt` account (after the
+/// 8-byte Anchor discriminator).
+#[derive(BorshSerialize, BorshDeserialize, Debug, Clone, PartialEq, Eq)]
+pub struct StakeDepositReceipt {
+    /// Owner of the position, the only key allowed to withdraw or claim
+    pub owner: Pubkey,
+    /// Account that paid rent for the receipt
+    pub payer: Pubkey,
+    /// Stake pool the deposit was made to
+    pub stake_pool: Pubkey,
+    /// Lockup duration in seconds
+    pub lockup_duration: u64,
+    /// When the deposit was made (Unix timestamp)
+    pub deposit_timestamp: i64,
+    /// Amount of BONK deposited (in lamports)
+    pub deposit_amount: u64,
+    /// Deposit amount scaled by the lockup weight
+    pub effective_stake: u128,
+    /// Per reward pool `rewards_per_effective_stake` at the last claim
+    pub claimed_amounts: [u128; MAX_REWARD_POOLS],
+}
+
+impl StakeDepositReceipt {
+    /// Account size in bytes, including the discriminator
+    pub const LEN: usize = 8 + 32 * 3 + 8 + 8 + 8 + 16 + 16 * MAX_REWARD_POOLS;
+
+    /// Byte offset of `owner` in the account data
+    pub const OWNER_OFFSET: usize = 8;
+
+    /// Byte offset of `stake_pool` in the account data
+    pub const STAKE_POOL_OFFSET: usize = 8 + 32 * 2;
+
+    /// Decode a receipt from raw account data
+    ///
+    /// Checks the 8-byte Anchor discriminator before deserializing.
+    pub fn try_deserialize(data: &[u8]) -> Result<Self> {
+        if data.len() < 8 || data[..8] != STAKE_DEPOSIT_RECEIPT_DISCRIMINATOR {
+            return Err(BonkStakingError::InvalidAccountData(
+                "Not a StakeDepositReceipt account".to_string(),
+            ));
+        }
+
+        let mut body = &data[8..];
+        Self::deserialize(&mut body).map_err(|_| BonkStakingError::DeserializationError)
+    }
+
+    /// When the stake unlocks (Unix timestamp)
+    pub fn unlock_at(&self) -> i64 {
+        self.deposit_timestamp + self.lockup_duration as i64
+    }
+}
+
 /// Information about a user's stake
 #[derive(Debug, Clone)]
 pub struct StakeInfo {
@@ -30,9 +185,24 @@ pub struct StakeInfo {
     pub created_at: i64,
     /// When the stake unlocks (Unix timestamp)
     pub unlock_at: i64,
+    /// Deposit amount scaled by the lockup weight
+    pub effective_stake: u128,
 }
 
 impl StakeInfo {
+    /// Build stake info from a decoded receipt
+    pub fn from_receipt(receipt_address: Pubkey, nonce: u32, receipt: &StakeDepositReceipt) -> Self {
+        Self {
+            receipt_address,
+            nonce,
+            amount: receipt.deposit_amount,
+            lock_duration: receipt.lockup_duration,
+            created_at: receipt.deposit_timestamp,
+            unlock_at: receipt.unlock_at(),
+            effective_stake: receipt.effective_stake,
+        }
+    }
+
     /// Check if the stake is currently locked
     pub fn is_locked(&self, current_time: i64) -> bool {
         current_time < self.unlock_at
@@ -42,4 +212,96 @@ impl StakeInfo {
     pub fn remaining_lock_time(&self, current_time: i64) -> i64 {
         (self.unlock_at - current_time).max(0)
     }
+}
+
+/// Rewards currently claimable for a stake position from one reward mint
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PendingReward {
+    /// Mint the rewards are paid in
+    pub mint: Pubkey,
+    /// Claimable amount (in the mint's smallest unit)
+    pub amount: u64,
+}
+
+/// Snapshot of a user's balances and stake positions
+#[derive(Debug, Clone)]
+pub struct Portfolio {
+    /// Balance of the pool's token (in the mint's smallest unit)
+    pub token_balance: u64,
+    /// Stake token balance (in the stake mint's smallest unit)
+    pub stake_balance: u64,
+    /// The stake pool at the time of the snapshot
+    pub stake_pool: StakePool,
+    /// The user's stake positions, ordered by nonce
+    pub stakes: Vec<StakeInfo>,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use borsh::to_vec;
+
+    fn sample_receipt() -> StakeDepositReceipt {
+        StakeDepositReceipt {
+            owner: Pubkey::new_unique(),
+            payer: Pubkey::new_unique(),
+            stake_pool: crate::BONK_STAKE_POOL,
+            lockup_duration: 15_552_000,
+            deposit_timestamp: 1_700_000_000,
+            deposit_amount: 10_000_000,
+            effective_stake: 20_000_000_000_000_000,
+            claimed_amounts: [0; MAX_REWARD_POOLS],
+        }
+    }
+
+    #[test]
+    fn test_stake_deposit_receipt_roundtrip() {
+        let receipt = sample_receipt();
+        let mut data = STAKE_DEPOSIT_RECEIPT_DISCRIMINATOR.to_vec();
+        data.extend(to_vec(&receipt).unwrap());
+
+        assert_eq!(data.len(), StakeDepositReceipt::LEN);
+        assert_eq!(StakeDepositReceipt::try_deserialize(&data).unwrap(), receipt);
+        assert_eq!(receipt.unlock_at(), 1_715_552_000);
+    }
+
+    #[test]
+    fn test_stake_pool_roundtrip() {
+        let mut reward_pools: [RewardPool; MAX_REWARD_POOLS] = Default::default();
+        reward_pools[0].reward_vault = crate::BONK_REWARD_VAULT_0;
+        reward_pools[0].last_amount = 42;
+
+        let pool = StakePool {
+            authority: Pubkey::new_unique(),
+            total_weighted_stake: 1_000_000_000_000,
+            vault: crate::BONK_VAULT,
+            mint: BONK_MINT,
+            stake_mint: BONK_STAKE_MINT,
+            reward_pools,
+            base_weight: 1_000_000_000,
+            max_weight: 4_000_000_000,
+            min_duration: 2_592_000,
+            max_duration: 31_536_000,
+            nonce: 0,
+            bump_seed: 255,
+        };
+
+        let mut data = STAKE_POOL_DISCRIMINATOR.to_vec();
+        data.extend(to_vec(&pool).unwrap());
+        // Trailing padding/reserved bytes are ignored
+        data.extend([0u8; 14]);
+
+        let decoded = StakePool::try_deserialize(&data).unwrap();
+        assert_eq!(decoded, pool);
+        assert_eq!(decoded.initialized_reward_pools().count(), 1);
+        assert!(StakeDepositReceipt::try_deserialize(&data).is_err());
+    }
+
+    #[test]
+    fn test_stake_deposit_receipt_rejects_wrong_discriminator() {
+        let mut data = vec![0u8; 8];
+        data.extend(to_vec(&sample_receipt()).unwrap());
+
+        assert!(StakeDepositReceipt::try_deserialize(&data).is_err());
+    }
 }
\ No newline at end of file
@@ -6,36 +6,22 @@
 // path = "src/bin/stake_manager.rs"
 
 use bonk_staking_rewards::{
-    derive_stake_deposit_receipt,
-    STAKE_PROGRAM_ID, BONK_STAKE_POOL, BONK_STAKE_MINT,
+    accounts::{StakeDepositReceipt, StakePool},
+    math::{check_effective_stake, stake_weight_for_pool, SCALE_FACTOR_BASE},
+    pda::derive_stake_deposit_receipt,
+    BONK_STAKE_POOL, BONK_STAKE_MINT,
 };
-use borsh::{BorshDeserialize, BorshSerialize};
 use solana_client::rpc_client::RpcClient;
 use solana_sdk::{
     commitment_config::CommitmentConfig,
-    pubkey::Pubkey,
     signature::{read_keypair_file, Signer},
 };
 use spl_associated_token_account::get_associated_token_address;
 use std::path::Path;
-use std::str::FromStr;
 
 const KEYPAIR_PATH: &str = "/Users/hogyzen12/.config/solana/6tBou5MHL5aWpDy6cgf3wiwGGK2mR8qs68ujtpaoWrf2.json";
 const RPC_URL: &str = "https://mainnet.helius-rpc.com/?api-key=93812d12-f56f-4624-97c9-9a4d242db974";
 
-// Stake receipt data structure (based on the program's IDL)
-#[derive(BorshSerialize, BorshDeserialize, Debug)]
-struct StakeDepositReceipt {
-    pub payer: Pubkey,
-    pub stake_pool: Pubkey,
-    pub lock_up_duration: u64,
-    pub deposit_timestamp: i64,
-    pub stake_mint_claimed: u64,
-    pub vault_claimed: u64,
-    pub effective_stake: u128,
-    pub effective_stake_pda_bump: u8,
-}
-
 fn format_duration(seconds: u64) -> String {
     let days = seconds / 86400;
     if days >= 365 {
@@ -97,13 +83,11 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
     // Create RPC client
     let client = RpcClient::new_with_commitment(RPC_URL.to_string(), CommitmentConfig::confirmed());
 
-    // Get program and pool addresses
-    let program_id = Pubkey::from_str(STAKE_PROGRAM_ID)?;
-    let stake_pool = Pubkey::from_str(BONK_STAKE_POOL)?;
+    // Fetch the stake pool for its weight parameters
+    let stake_pool = StakePool::try_deserialize(&client.get_account(&BONK_STAKE_POOL)?.data)?;
 
     // Check sBONK balance
-    let stake_mint = Pubkey::from_str(BONK_STAKE_MINT)?;
-    let stake_token_account = get_associated_token_address(&owner, &stake_mint);
+    let stake_token_account = get_associated_token_address(&owner, &BONK_STAKE_MINT);
     
     match client.get_token_account_balance(&stake_token_account) {
         Ok(balance) => {
@@ -124,9 +108,8 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
     for nonce in 0..20 {
         let (receipt_pda, _bump) = derive_stake_deposit_receipt(
             &owner,
-            &stake_pool,
+            &BONK_STAKE_POOL,
             nonce,
-            &program_id,
         );
         
         match client.get_account(&receipt_pda) {
@@ -134,28 +117,30 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
                 active_stakes += 1;
                 
                 // Try to deserialize the account data
-                match StakeDepositReceipt::try_from_slice(&account.data[8..]) {
+                match StakeDepositReceipt::try_deserialize(&account.data) {
                     Ok(receipt) => {
                         println!("\n📌 Stake Position #{}", nonce);
                         println!("   Receipt: {}", receipt_pda);
                         println!("   ────────────────────────────────");
-                        println!("   Amount staked:    {} BONK", receipt.vault_claimed as f64 / 100_000.0);
-                        println!("   sBONK received:   {} sBONK", receipt.stake_mint_claimed as f64 / 100_000.0);
+                        println!("   Amount staked:    {} BONK", receipt.deposit_amount as f64 / 100_000.0);
                         println!("   Effective stake:  {}", receipt.effective_stake);
-                        println!("   Lock duration:    {}", format_duration(receipt.lock_up_duration));
+                        println!("   Lock duration:    {}", format_duration(receipt.lockup_duration));
                         println!("   Staked on:        {}", format_timestamp(receipt.deposit_timestamp));
-                        println!("   Status:           {}", calculate_unlock_date(receipt.deposit_timestamp, receipt.lock_up_duration));
+                        println!("   Status:           {}", calculate_unlock_date(receipt.deposit_timestamp, receipt.lockup_duration));
                         
-                        total_staked += receipt.vault_claimed;
+                        total_staked += receipt.deposit_amount;
                         
-                        // Calculate multiplier based on duration
-                        let multiplier = match receipt.lock_up_duration {
-                            d if d <= 2_592_000 => "1.0x",
-                            d if d <= 7_776_000 => "1.5x",
-                            d if d <= 15_552_000 => "2.25x",
-                            _ => "3.2x",
-                        };
-                        println!("   Multiplier:       {}", multiplier);
+                        // Calculate multiplier from the pool's weight formula
+                        match stake_weight_for_pool(&stake_pool, receipt.lockup_duration) {
+                            Some(weight) => println!(
+                                "   Multiplier:       {:.2}x",
+                                weight as f64 / SCALE_FACTOR_BASE as f64
+                            ),
+                            None => println!("   Multiplier:       unknown (lockup outside pool range)"),
+                        }
+                        if let Err(e) = check_effective_stake(&receipt, &stake_pool) {
+                            println!("   ⚠️  {}", e);
+                        }
                     }
                     Err(e) => {
                         println!("\n📌 Stake Position #{}", nonce);
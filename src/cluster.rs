@@ -0,0 +1,239 @@
+//! Named cluster profiles
+//!
+//! A [`ClusterProfile`] bundles an RPC endpoint with the stake pool to use on
+//! it. `mainnet` and `localnet` are built in. There is no canonical pool on
+//! devnet, so a `devnet` profile, like any other, is loaded from a TOML file:
+//!
+//! ```toml
+//! [profiles.devnet]
+//! rpc_url = "https://api.devnet.solana.com"
+//! # Optional, defaults to the spl-token-staking program
+//! program_id = "STAKEkKzbdeKkqzKpLkNQD3SUuLgshDKCD7U8duxAbB"
+//! stake_pool = "..."
+//! vault = "..."
+//! mint = "..."
+//! stake_mint = "..."
+//! reward_vaults = ["..."]
+//! decimals = 5
+//! ```
+
+use std::{collections::BTreeMap, fs, path::Path, str::FromStr};
+
+use serde::Deserialize;
+use solana_sdk::pubkey::Pubkey;
+
+use crate::{
+    error::{BonkStakingError, Result},
+    pool::PoolConfig,
+    BONK_STAKE_PROGRAM_ID,
+};
+
+/// Public mainnet-beta RPC endpoint
+pub const MAINNET_RPC_URL: &str = "https://api.mainnet-beta.solana.com";
+
+/// Public devnet RPC endpoint
+pub const DEVNET_RPC_URL: &str = "https://api.devnet.solana.com";
+
+/// Default `solana-test-validator` RPC endpoint
+pub const LOCALNET_RPC_URL: &str = "http://127.0.0.1:8899";
+
+/// An RPC endpoint and the stake pool to use on it
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ClusterProfile {
+    /// Profile name
+    pub name: String,
+    /// RPC endpoint URL
+    pub rpc_url: String,
+    /// Program and pool addresses on this cluster
+    pub pool: PoolConfig,
+}
+
+impl ClusterProfile {
+    /// The BONK pool on mainnet-beta
+    pub fn mainnet() -> Self {
+        Self {
+            name: "mainnet".to_string(),
+            rpc_url: MAINNET_RPC_URL.to_string(),
+            pool: PoolConfig::bonk_mainnet(),
+        }
+    }
+
+    /// A local validator with the mainnet program and pool accounts cloned in
+    ///
+    /// E.g. `solana-test-validator --url mainnet-beta --clone-upgradeable-program <program> --clone <pool> ...`
+    pub fn localnet() -> Self {
+        Self {
+            name: "localnet".to_string(),
+            rpc_url: LOCALNET_RPC_URL.to_string(),
+            pool: PoolConfig::bonk_mainnet(),
+        }
+    }
+
+    /// Look up a built-in profile by name
+    ///
+    /// Accepts `mainnet` (or `mainnet-beta`) and `localnet` (or `localhost`).
+    pub fn builtin(name: &str) -> Option<Self> {
+        match name {
+            "mainnet" | "mainnet-beta" => Some(Self::mainnet()),
+            "localnet" | "localhost" => Some(Self::localnet()),
+            _ => None,
+        }
+    }
+
+    /// Look up a profile by name, in a profile file first and then the built-ins
+    ///
+    /// # Arguments
+    /// * `name` - Profile name
+    /// * `path` - Optional TOML profile file
+    pub fn find(name: &str, path: Option<&Path>) -> Result<Self> {
+        if let Some(path) = path {
+            if let Some(profile) = load_profiles(path)?.into_iter().find(|profile| profile.name == name) {
+                return Ok(profile);
+            }
+        }
+
+        if let Some(profile) = Self::builtin(name) {
+            return Ok(profile);
+        }
+
+        Err(BonkStakingError::InvalidConfig(match name {
+            "devnet" | "testnet" => format!(
+                "No built-in {} pool, define a [profiles.{}] entry in a profile file",
+                name, name
+            ),
+            _ => format!("Unknown cluster profile: {}", name),
+        }))
+    }
+}
+
+/// Load custom profiles from a TOML file
+pub fn load_profiles(path: impl AsRef<Path>) -> Result<Vec<ClusterProfile>> {
+    let path = path.as_ref();
+    let contents = fs::read_to_string(path).map_err(|e| {
+        BonkStakingError::InvalidConfig(format!("Can't read profile file {}: {}", path.display(), e))
+    })?;
+
+    parse_profiles(&contents)
+}
+
+/// Parse custom profiles from TOML, ordered by name
+pub fn parse_profiles(contents: &str) -> Result<Vec<ClusterProfile>> {
+    let file: ProfileFile =
+        toml::from_str(contents).map_err(|e| BonkStakingError::InvalidConfig(e.to_string()))?;
+
+    file.profiles
+        .into_iter()
+        .map(|(name, profile)| profile.into_profile(name))
+        .collect()
+}
+
+/// Layout of a profile file
+#[derive(Deserialize)]
+struct ProfileFile {
+    #[serde(default)]
+    profiles: BTreeMap<String, ProfileEntry>,
+}
+
+/// A profile as written in TOML, with addresses as base58 strings
+#[derive(Deserialize)]
+struct ProfileEntry {
+    rpc_url: String,
+    program_id: Option<String>,
+    stake_pool: String,
+    vault: String,
+    mint: String,
+    stake_mint: String,
+    #[serde(default)]
+    reward_vaults: Vec<String>,
+    decimals: u8,
+}
+
+impl ProfileEntry {
+    fn into_profile(self, name: String) -> Result<ClusterProfile> {
+        let program_id = match &self.program_id {
+            Some(program_id) => parse_pubkey(&name, "program_id", program_id)?,
+            None => BONK_STAKE_PROGRAM_ID,
+        };
+        let reward_vaults = self
+            .reward_vaults
+            .iter()
+            .map(|vault| parse_pubkey(&name, "reward_vaults", vault))
+            .collect::<Result<Vec<_>>>()?;
+
+        let pool = PoolConfig {
+            program_id,
+            stake_pool: parse_pubkey(&name, "stake_pool", &self.stake_pool)?,
+            vault: parse_pubkey(&name, "vault", &self.vault)?,
+            mint: parse_pubkey(&name, "mint", &self.mint)?,
+            stake_mint: parse_pubkey(&name, "stake_mint", &self.stake_mint)?,
+            reward_vaults,
+            decimals: self.decimals,
+        };
+
+        Ok(ClusterProfile {
+            name,
+            rpc_url: self.rpc_url,
+            pool,
+        })
+    }
+}
+
+fn parse_pubkey(profile: &str, field: &str, value: &str) -> Result<Pubkey> {
+    Pubkey::from_str(value).map_err(|_| {
+        BonkStakingError::InvalidConfig(format!("Profile {}: invalid {} address {}", profile, field, value))
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{BONK_MINT, BONK_REWARD_VAULT_0, BONK_STAKE_MINT, BONK_STAKE_POOL, BONK_VAULT};
+
+    #[test]
+    fn test_parse_profiles() {
+        let contents = format!(
+            r#"
+            [profiles.staging]
+            rpc_url = "http://127.0.0.1:8899"
+            stake_pool = "{}"
+            vault = "{}"
+            mint = "{}"
+            stake_mint = "{}"
+            reward_vaults = ["{}"]
+            decimals = 5
+            "#,
+            BONK_STAKE_POOL, BONK_VAULT, BONK_MINT, BONK_STAKE_MINT, BONK_REWARD_VAULT_0
+        );
+
+        let profiles = parse_profiles(&contents).unwrap();
+        assert_eq!(profiles.len(), 1);
+        assert_eq!(profiles[0].name, "staging");
+        assert_eq!(profiles[0].rpc_url, LOCALNET_RPC_URL);
+        assert_eq!(profiles[0].pool, PoolConfig::bonk_mainnet());
+
+        let invalid = contents.replace(&BONK_VAULT.to_string(), "not-a-pubkey");
+        assert!(matches!(parse_profiles(&invalid), Err(BonkStakingError::InvalidConfig(_))));
+    }
+
+    #[test]
+    fn test_missing_profile_file() {
+        match load_profiles("does/not/exist.toml") {
+            Err(BonkStakingError::InvalidConfig(message)) => assert!(message.contains("does/not/exist.toml")),
+            other => panic!("unexpected result: {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_builtin_profiles() {
+        assert_eq!(ClusterProfile::find("mainnet-beta", None).unwrap(), ClusterProfile::mainnet());
+        assert_eq!(ClusterProfile::find("localnet", None).unwrap().rpc_url, LOCALNET_RPC_URL);
+        assert!(matches!(
+            ClusterProfile::find("devnet", None),
+            Err(BonkStakingError::InvalidConfig(_))
+        ));
+        assert!(matches!(
+            ClusterProfile::find("staging", None),
+            Err(BonkStakingError::InvalidConfig(_))
+        ));
+    }
+}
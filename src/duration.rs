@@ -0,0 +1,118 @@
+//! Lock duration type for BONK staking
+
+use crate::{
+    accounts::StakePool,
+    error::{BonkStakingError, Result},
+    DURATION_12_MONTHS, DURATION_1_MONTH, DURATION_3_MONTHS, DURATION_6_MONTHS,
+};
+
+/// Number of seconds in a day
+pub const SECONDS_PER_DAY: u64 = 24 * 60 * 60;
+
+/// Lockup duration for a stake position
+///
+/// The program accepts any lockup between the pool's `min_duration` and
+/// `max_duration`, so this can be built from days, seconds or one of the presets.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct LockDuration {
+    seconds: u64,
+}
+
+impl LockDuration {
+    /// 1 month (30 days)
+    pub const ONE_MONTH: Self = Self::from_days(DURATION_1_MONTH);
+
+    /// 3 months (90 days)
+    pub const THREE_MONTHS: Self = Self::from_days(DURATION_3_MONTHS);
+
+    /// 6 months (180 days)
+    pub const SIX_MONTHS: Self = Self::from_days(DURATION_6_MONTHS);
+
+    /// 12 months (365 days)
+    pub const TWELVE_MONTHS: Self = Self::from_days(DURATION_12_MONTHS);
+
+    /// Create a lock duration from a number of seconds
+    pub const fn from_seconds(seconds: u64) -> Self {
+        Self { seconds }
+    }
+
+    /// Create a lock duration from a number of days
+    pub const fn from_days(days: u64) -> Self {
+        Self {
+            seconds: days.saturating_mul(SECONDS_PER_DAY),
+        }
+    }
+
+    /// Get the duration in seconds
+    pub fn as_seconds(&self) -> u64 {
+        self.seconds
+    }
+
+    /// Get the duration in whole days
+    pub fn as_days(&self) -> u64 {
+        self.seconds / SECONDS_PER_DAY
+    }
+
+    /// Validate the duration against the pool's lockup bounds
+    ///
+    /// # Arguments
+    /// * `stake_pool` - The stake pool the deposit is made to
+    ///
+    /// # Returns
+    /// The duration in seconds, ready for the deposit instruction
+    pub fn validate(&self, stake_pool: &StakePool) -> Result<u64> {
+        if self.seconds < stake_pool.min_duration || self.seconds > stake_pool.max_duration {
+            return Err(BonkStakingError::InvalidDuration(format!(
+                "Duration must be between {} and {} days ({} and {} seconds), got {} seconds",
+                stake_pool.min_duration / SECONDS_PER_DAY,
+                stake_pool.max_duration / SECONDS_PER_DAY,
+                stake_pool.min_duration,
+                stake_pool.max_duration,
+                self.seconds,
+            )));
+        }
+
+        Ok(self.seconds)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use solana_sdk::pubkey::Pubkey;
+
+    fn sample_pool() -> StakePool {
+        StakePool {
+            authority: Pubkey::default(),
+            total_weighted_stake: 0,
+            vault: Pubkey::default(),
+            mint: Pubkey::default(),
+            stake_mint: Pubkey::default(),
+            reward_pools: Default::default(),
+            base_weight: 1_000_000_000,
+            max_weight: 4_000_000_000,
+            min_duration: LockDuration::ONE_MONTH.as_seconds(),
+            max_duration: LockDuration::TWELVE_MONTHS.as_seconds(),
+            nonce: 0,
+            bump_seed: 0,
+        }
+    }
+
+    #[test]
+    fn test_lock_duration_conversions() {
+        assert_eq!(LockDuration::SIX_MONTHS.as_seconds(), 15_552_000);
+        assert_eq!(LockDuration::from_days(45), LockDuration::from_seconds(3_888_000));
+        assert_eq!(LockDuration::from_seconds(3_888_000).as_days(), 45);
+    }
+
+    #[test]
+    fn test_lock_duration_validation() {
+        let pool = sample_pool();
+
+        assert_eq!(LockDuration::from_days(270).validate(&pool).unwrap(), 23_328_000);
+        assert!(LockDuration::ONE_MONTH.validate(&pool).is_ok());
+        assert!(LockDuration::TWELVE_MONTHS.validate(&pool).is_ok());
+        assert!(LockDuration::from_days(29).validate(&pool).is_err());
+        assert!(LockDuration::from_days(366).validate(&pool).is_err());
+    }
+}
@@ -2,15 +2,17 @@
 
 use thiserror::Error;
 
+use crate::{pool::PoolConfigDiff, program_error::ProgramErrorKind};
+
 /// Result type for BONK staking operations
 pub type Result<T> = std::result::Result<T, BonkStakingError>;
 
 /// Errors that can occur when using the BONK Staking client
 #[derive(Debug, Error)]
 pub enum BonkStakingError {
-    /// Error from the Solana client
+    /// Error from the Solana client, boxed to keep `Result`s small
     #[error("Solana client error: {0}")]
-    ClientError(#[from] solana_client::client_error::ClientError),
+    ClientError(Box<solana_client::client_error::ClientError>),
 
     /// Failed to deserialize account data
     #[error("Failed to deserialize account data")]
@@ -32,6 +34,10 @@ pub enum BonkStakingError {
     #[error("Transaction failed: {0}")]
     TransactionFailed(String),
 
+    /// The transaction's blockhash expired before it landed
+    #[error("Transaction expired before landing: {0}")]
+    TransactionExpired(String),
+
     /// Insufficient balance
     #[error("Insufficient balance: required {required}, available {available}")]
     InsufficientBalance { required: u64, available: u64 },
@@ -48,11 +54,41 @@ pub enum BonkStakingError {
     #[error("Invalid nonce: {0}")]
     InvalidNonce(String),
 
+    /// Stake position is still within its lockup period
+    #[error("Stake is locked until {unlock_at} (unix timestamp)")]
+    StakeLocked { unlock_at: i64 },
+
+    /// The staking program (or Anchor) rejected the transaction
+    #[error("Program error {code}: {kind:?}")]
+    Program {
+        code: u32,
+        kind: ProgramErrorKind,
+        logs: Vec<String>,
+    },
+
+    /// Transaction simulation failed
+    #[error("Simulation failed: {message}")]
+    SimulationFailed { message: String, logs: Vec<String> },
+
+    /// Invalid cluster profile or pool configuration
+    #[error("Invalid configuration: {0}")]
+    InvalidConfig(String),
+
+    /// The configured pool doesn't match the pool on chain
+    #[error("Pool config does not match the chain: {0}")]
+    PoolConfigMismatch(PoolConfigDiff),
+
     /// PDA derivation error
     #[error("Failed to derive PDA: {0}")]
     PdaDerivationError(String),
 }
 
+impl From<solana_client::client_error::ClientError> for BonkStakingError {
+    fn from(err: solana_client::client_error::ClientError) -> Self {
+        BonkStakingError::ClientError(Box::new(err))
+    }
+}
+
 impl From<std::io::Error> for BonkStakingError {
     fn from(err: std::io::Error) -> Self {
         BonkStakingError::SerializationError(err)
@@ -0,0 +1,130 @@
+//! Priority fee strategies and compute unit limits
+//!
+//! The client prices compute units either at a fixed rate or from the fees
+//! recently paid by transactions writing to the stake pool's accounts, as
+//! reported by `getRecentPrioritizationFees`. The number of units it pays for
+//! is sized by simulating the transaction first.
+
+use solana_sdk::pubkey::Pubkey;
+
+use crate::pool::PoolConfig;
+
+/// Compute unit price used by default (in micro-lamports)
+///
+/// Taken from a historical successful stake transaction.
+pub const DEFAULT_PRIORITY_FEE: u64 = 5045;
+
+/// Highest compute unit limit a transaction can request
+pub const MAX_COMPUTE_UNIT_LIMIT: u32 = 1_400_000;
+
+/// Extra compute units requested on top of the simulated usage, in percent
+pub const DEFAULT_COMPUTE_UNIT_MARGIN: u32 = 10;
+
+/// How the compute unit price of a transaction is chosen
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PriorityFeeStrategy {
+    /// Always pay this many micro-lamports per compute unit
+    Fixed(u64),
+    /// Pay the given percentile (0-100) of recent prioritization fees
+    Percentile(u8),
+    /// Pay the given percentile of recent fees, but never more than `max_micro_lamports`
+    Capped {
+        /// Percentile (0-100) of recent prioritization fees
+        percentile: u8,
+        /// Upper bound on the compute unit price (in micro-lamports)
+        max_micro_lamports: u64,
+    },
+}
+
+impl Default for PriorityFeeStrategy {
+    fn default() -> Self {
+        PriorityFeeStrategy::Fixed(DEFAULT_PRIORITY_FEE)
+    }
+}
+
+impl PriorityFeeStrategy {
+    /// Whether the strategy needs recent prioritization fees
+    pub fn needs_recent_fees(&self) -> bool {
+        !matches!(self, PriorityFeeStrategy::Fixed(_))
+    }
+
+    /// Pick the compute unit price
+    ///
+    /// # Arguments
+    /// * `recent_fees` - Recent prioritization fees (ignored by `Fixed`)
+    ///
+    /// # Returns
+    /// Compute unit price in micro-lamports
+    pub fn micro_lamports(&self, recent_fees: &[u64]) -> u64 {
+        match *self {
+            PriorityFeeStrategy::Fixed(micro_lamports) => micro_lamports,
+            PriorityFeeStrategy::Percentile(percentile) => fee_percentile(recent_fees, percentile),
+            PriorityFeeStrategy::Capped {
+                percentile,
+                max_micro_lamports,
+            } => fee_percentile(recent_fees, percentile).min(max_micro_lamports),
+        }
+    }
+}
+
+/// Get a percentile of fee samples (nearest rank), 0 if there are none
+pub fn fee_percentile(fees: &[u64], percentile: u8) -> u64 {
+    if fees.is_empty() {
+        return 0;
+    }
+
+    let mut sorted = fees.to_vec();
+    sorted.sort_unstable();
+    let percentile = percentile.min(100) as usize;
+    sorted[(sorted.len() - 1) * percentile / 100]
+}
+
+/// Size a compute unit limit from a simulation
+///
+/// # Arguments
+/// * `units_consumed` - Compute units consumed by the simulation
+/// * `margin_percent` - Extra units to add, in percent of `units_consumed`
+pub fn compute_unit_limit(units_consumed: u64, margin_percent: u32) -> u32 {
+    let units = units_consumed.saturating_mul(100 + margin_percent as u64) / 100;
+    units.min(MAX_COMPUTE_UNIT_LIMIT as u64) as u32
+}
+
+/// Writable accounts shared by every stake, withdraw and claim transaction
+///
+/// # Arguments
+/// * `pool` - The stake pool, including its initialized reward vaults
+pub fn pool_writable_accounts(pool: &PoolConfig) -> Vec<Pubkey> {
+    let mut accounts = vec![pool.stake_pool, pool.vault, pool.stake_mint];
+    accounts.extend_from_slice(&pool.reward_vaults);
+    accounts
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_fee_strategies() {
+        let fees = [0, 10, 20, 30, 40, 50, 60, 70, 80, 90, 100];
+
+        assert_eq!(PriorityFeeStrategy::default().micro_lamports(&fees), DEFAULT_PRIORITY_FEE);
+        assert_eq!(PriorityFeeStrategy::Percentile(50).micro_lamports(&fees), 50);
+        assert_eq!(PriorityFeeStrategy::Percentile(100).micro_lamports(&fees), 100);
+        assert_eq!(PriorityFeeStrategy::Percentile(75).micro_lamports(&[]), 0);
+        assert_eq!(
+            PriorityFeeStrategy::Capped {
+                percentile: 90,
+                max_micro_lamports: 60,
+            }
+            .micro_lamports(&fees),
+            60
+        );
+    }
+
+    #[test]
+    fn test_compute_unit_limit() {
+        assert_eq!(compute_unit_limit(60_000, DEFAULT_COMPUTE_UNIT_MARGIN), 66_000);
+        assert_eq!(compute_unit_limit(60_000, 0), 60_000);
+        assert_eq!(compute_unit_limit(1_300_000, 50), MAX_COMPUTE_UNIT_LIMIT);
+    }
+}
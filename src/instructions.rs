@@ -8,9 +8,10 @@ use solana_sdk::{
 use spl_token;
 
 use crate::{
-    accounts::{get_user_bonk_ata, get_user_stake_ata},
-    pda::derive_stake_deposit_receipt,
-    BONK_REWARD_VAULT_0, BONK_STAKE_MINT, BONK_STAKE_POOL, BONK_STAKE_PROGRAM_ID, BONK_VAULT,
+    accounts::{get_user_pool_stake_ata, get_user_pool_token_ata},
+    pda::{derive_reward_vault, derive_stake_mint, derive_stake_pool, derive_vault},
+    pool::PoolConfig,
+    BONK_REWARD_VAULT_0,
 };
 
 /// Build the deposit (stake) instruction
@@ -28,13 +29,94 @@ pub fn build_stake_instruction(
     amount: u64,
     lock_duration: u64,
     nonce: u32,
+) -> Instruction {
+    build_stake_instruction_with_reward_vaults(
+        user,
+        amount,
+        lock_duration,
+        nonce,
+        &[BONK_REWARD_VAULT_0],
+    )
+}
+
+/// Build the deposit (stake) instruction with an explicit reward vault list
+///
+/// The program requires the vault of every initialized reward pool, in the same
+/// order as StakePool.reward_pools.
+///
+/// # Arguments
+/// * `user` - The user's public key
+/// * `amount` - Amount of BONK to stake (in lamports, not UI amount)
+/// * `lock_duration` - Lock duration in seconds
+/// * `nonce` - Nonce for the stake deposit receipt PDA
+/// * `reward_vaults` - Reward pool vaults, in StakePool.reward_pools order
+///
+/// # Returns
+/// The stake deposit instruction
+pub fn build_stake_instruction_with_reward_vaults(
+    user: &Pubkey,
+    amount: u64,
+    lock_duration: u64,
+    nonce: u32,
+    reward_vaults: &[Pubkey],
+) -> Instruction {
+    build_stake_instruction_with_payer(user, user, amount, lock_duration, nonce, reward_vaults)
+}
+
+/// Build the deposit (stake) instruction with a separate rent payer
+///
+/// The payer funds the stake deposit receipt's rent, while the owner's BONK is
+/// staked and the position (and its stake tokens) belong to the owner. Both
+/// must sign.
+///
+/// # Arguments
+/// * `payer` - Account paying the receipt's rent
+/// * `owner` - The position owner's public key
+/// * `amount` - Amount of BONK to stake (in lamports, not UI amount)
+/// * `lock_duration` - Lock duration in seconds
+/// * `nonce` - Nonce for the stake deposit receipt PDA
+/// * `reward_vaults` - Reward pool vaults, in StakePool.reward_pools order
+///
+/// # Returns
+/// The stake deposit instruction
+pub fn build_stake_instruction_with_payer(
+    payer: &Pubkey,
+    owner: &Pubkey,
+    amount: u64,
+    lock_duration: u64,
+    nonce: u32,
+    reward_vaults: &[Pubkey],
+) -> Instruction {
+    let pool = PoolConfig::bonk_mainnet().with_reward_vaults(reward_vaults.to_vec());
+    build_stake_instruction_for_pool(&pool, payer, owner, amount, lock_duration, nonce)
+}
+
+/// Build the deposit (stake) instruction for any stake pool
+///
+/// # Arguments
+/// * `pool` - The stake pool, including its reward vaults
+/// * `payer` - Account paying the receipt's rent
+/// * `owner` - The position owner's public key
+/// * `amount` - Amount of tokens to stake (in base units, not UI amount)
+/// * `lock_duration` - Lock duration in seconds
+/// * `nonce` - Nonce for the stake deposit receipt PDA
+///
+/// # Returns
+/// The stake deposit instruction
+pub fn build_stake_instruction_for_pool(
+    pool: &PoolConfig,
+    payer: &Pubkey,
+    owner: &Pubkey,
+    amount: u64,
+    lock_duration: u64,
+    nonce: u32,
 ) -> Instruction {
     // Derive the stake deposit receipt PDA
-    let (stake_deposit_receipt, _) = derive_stake_deposit_receipt(user, &BONK_STAKE_POOL, nonce);
+    let (stake_deposit_receipt, _) = pool.derive_receipt(owner, nonce);
 
     // Get token accounts
-    let user_bonk_ata = get_user_bonk_ata(user);
-    let user_stake_ata = get_user_stake_ata(user);
+    let user_token_ata = get_user_pool_token_ata(owner, pool);
+    let user_stake_ata = get_user_pool_stake_ata(owner, pool);
 
     // Build instruction data
     // Format: [discriminator(8), nonce(4), amount(8), lockupDuration(8)]
@@ -54,13 +136,13 @@ pub fn build_stake_instruction(
 
     // Build accounts list
     let mut accounts = vec![
-        AccountMeta::new(*user, true),                      // payer
-        AccountMeta::new(*user, true),                      // owner
-        AccountMeta::new(user_bonk_ata, false),            // from (user's BONK ATA)
-        AccountMeta::new(BONK_VAULT, false),               // vault
-        AccountMeta::new(BONK_STAKE_MINT, false),          // stake_mint
+        AccountMeta::new(*payer, true),                     // payer
+        AccountMeta::new(*owner, true),                     // owner
+        AccountMeta::new(user_token_ata, false),           // from (user's token ATA)
+        AccountMeta::new(pool.vault, false),               // vault
+        AccountMeta::new(pool.stake_mint, false),          // stake_mint
         AccountMeta::new(user_stake_ata, false),           // destination (user's stake ATA)
-        AccountMeta::new(BONK_STAKE_POOL, false),          // stake_pool
+        AccountMeta::new(pool.stake_pool, false),          // stake_pool
         AccountMeta::new(stake_deposit_receipt, false),    // stake_deposit_receipt
         AccountMeta::new_readonly(spl_token::id(), false), // token_program
         AccountMeta::new_readonly(sysvar::rent::id(), false), // rent
@@ -69,10 +151,272 @@ pub fn build_stake_instruction(
     
     // Add remaining accounts: reward pool vaults (required by the program)
     // These must be in the same order as StakePool.reward_pools
-    accounts.push(AccountMeta::new(BONK_REWARD_VAULT_0, false));
+    for reward_vault in &pool.reward_vaults {
+        accounts.push(AccountMeta::new(*reward_vault, false));
+    }
 
     Instruction {
-        program_id: BONK_STAKE_PROGRAM_ID,
+        program_id: pool.program_id,
+        accounts,
+        data,
+    }
+}
+
+/// Build the withdraw instruction
+///
+/// Withdraws an unlocked stake position: burns the stake tokens, returns the
+/// staked BONK from the vault and pays out any outstanding rewards.
+///
+/// # Arguments
+/// * `owner` - The owner's public key
+/// * `nonce` - Nonce of the stake deposit receipt being withdrawn
+/// * `destination` - Token account receiving the unstaked BONK
+/// * `from` - Stake token account the stake tokens are burned from
+/// * `reward_accounts` - Pairs of (reward vault, owner's reward token account)
+///
+/// # Returns
+/// The withdraw instruction
+pub fn build_withdraw_instruction(
+    owner: &Pubkey,
+    nonce: u32,
+    destination: &Pubkey,
+    from: &Pubkey,
+    reward_accounts: &[(Pubkey, Pubkey)],
+) -> Instruction {
+    build_withdraw_instruction_for_pool(
+        &PoolConfig::bonk_mainnet(),
+        owner,
+        nonce,
+        destination,
+        from,
+        reward_accounts,
+    )
+}
+
+/// Build the withdraw instruction for any stake pool
+///
+/// # Arguments
+/// * `pool` - The stake pool
+/// * `owner` - The owner's public key
+/// * `nonce` - Nonce of the stake deposit receipt being withdrawn
+/// * `destination` - Token account receiving the unstaked tokens
+/// * `from` - Stake token account the stake tokens are burned from
+/// * `reward_accounts` - Pairs of (reward vault, owner's reward token account)
+///
+/// # Returns
+/// The withdraw instruction
+pub fn build_withdraw_instruction_for_pool(
+    pool: &PoolConfig,
+    owner: &Pubkey,
+    nonce: u32,
+    destination: &Pubkey,
+    from: &Pubkey,
+    reward_accounts: &[(Pubkey, Pubkey)],
+) -> Instruction {
+    // Derive the stake deposit receipt PDA
+    let (stake_deposit_receipt, _) = pool.derive_receipt(owner, nonce);
+
+    // Discriminator for "withdraw" instruction (from IDL), no arguments
+    let data = vec![183, 18, 70, 156, 148, 109, 161, 34];
+
+    // Build accounts list
+    let mut accounts = vec![
+        AccountMeta::new(*owner, true),                     // claim_base.owner
+        AccountMeta::new(pool.stake_pool, false),          // claim_base.stake_pool
+        AccountMeta::new(stake_deposit_receipt, false),    // claim_base.stake_deposit_receipt
+        AccountMeta::new_readonly(spl_token::id(), false), // claim_base.token_program
+        AccountMeta::new(pool.vault, false),               // vault
+        AccountMeta::new(pool.stake_mint, false),          // stake_mint
+        AccountMeta::new(*from, false),                    // from (stake tokens to burn)
+        AccountMeta::new(*destination, false),             // destination (unstaked tokens)
+    ];
+
+    // Add remaining accounts: (reward vault, owner reward account) pairs
+    // These must be in the same order as StakePool.reward_pools
+    for (reward_vault, owner_reward_account) in reward_accounts {
+        accounts.push(AccountMeta::new(*reward_vault, false));
+        accounts.push(AccountMeta::new(*owner_reward_account, false));
+    }
+
+    Instruction {
+        program_id: pool.program_id,
+        accounts,
+        data,
+    }
+}
+
+/// Build the claim all (rewards) instruction
+///
+/// Claims accrued rewards from every reward pool into the owner's reward
+/// token accounts.
+///
+/// # Arguments
+/// * `owner` - The owner's public key
+/// * `nonce` - Nonce of the stake deposit receipt to claim for
+/// * `reward_accounts` - Pairs of (reward vault, owner's reward token account)
+///
+/// # Returns
+/// The claim all instruction
+pub fn build_claim_all_instruction(
+    owner: &Pubkey,
+    nonce: u32,
+    reward_accounts: &[(Pubkey, Pubkey)],
+) -> Instruction {
+    build_claim_all_instruction_for_pool(&PoolConfig::bonk_mainnet(), owner, nonce, reward_accounts)
+}
+
+/// Build the claim all (rewards) instruction for any stake pool
+///
+/// # Arguments
+/// * `pool` - The stake pool
+/// * `owner` - The owner's public key
+/// * `nonce` - Nonce of the stake deposit receipt to claim for
+/// * `reward_accounts` - Pairs of (reward vault, owner's reward token account)
+///
+/// # Returns
+/// The claim all instruction
+pub fn build_claim_all_instruction_for_pool(
+    pool: &PoolConfig,
+    owner: &Pubkey,
+    nonce: u32,
+    reward_accounts: &[(Pubkey, Pubkey)],
+) -> Instruction {
+    // Derive the stake deposit receipt PDA
+    let (stake_deposit_receipt, _) = pool.derive_receipt(owner, nonce);
+
+    // Discriminator for "claim_all" instruction (from IDL), no arguments
+    let data = vec![194, 194, 80, 194, 234, 210, 217, 90];
+
+    // Build accounts list
+    let mut accounts = vec![
+        AccountMeta::new(*owner, true),                     // claim_base.owner
+        AccountMeta::new(pool.stake_pool, false),          // claim_base.stake_pool
+        AccountMeta::new(stake_deposit_receipt, false),    // claim_base.stake_deposit_receipt
+        AccountMeta::new_readonly(spl_token::id(), false), // claim_base.token_program
+    ];
+
+    // Add remaining accounts: (reward vault, owner reward account) pairs
+    // These must be in the same order as StakePool.reward_pools
+    for (reward_vault, owner_reward_account) in reward_accounts {
+        accounts.push(AccountMeta::new(*reward_vault, false));
+        accounts.push(AccountMeta::new(*owner_reward_account, false));
+    }
+
+    Instruction {
+        program_id: pool.program_id,
+        accounts,
+        data,
+    }
+}
+
+/// Settings of a new stake pool
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct StakePoolParams {
+    /// Nonce distinguishing pools with the same mint and authority
+    pub nonce: u8,
+    /// Weight of a maximum-duration stake, scaled by 1e9 (1x = 1_000_000_000)
+    pub max_weight: u64,
+    /// Shortest allowed lockup, in seconds
+    pub min_duration: u64,
+    /// Longest allowed lockup, in seconds
+    pub max_duration: u64,
+}
+
+/// Build the init stake pool instruction
+///
+/// Creates the stake pool together with its vault and stake mint PDAs. The
+/// authority doesn't sign here, but must sign to add reward pools later.
+///
+/// # Arguments
+/// * `program_id` - The staking program ID
+/// * `payer` - Account paying the rent of the new accounts
+/// * `authority` - The pool authority
+/// * `mint` - The token to stake
+/// * `params` - Nonce, weight and lockup bounds of the pool
+///
+/// # Returns
+/// The init stake pool instruction
+pub fn build_init_stake_pool_instruction(
+    program_id: &Pubkey,
+    payer: &Pubkey,
+    authority: &Pubkey,
+    mint: &Pubkey,
+    params: &StakePoolParams,
+) -> Instruction {
+    // Derive the pool and its PDAs
+    let (stake_pool, _) = derive_stake_pool(params.nonce, mint, authority, program_id);
+    let (stake_mint, _) = derive_stake_mint(&stake_pool, program_id);
+    let (vault, _) = derive_vault(&stake_pool, program_id);
+
+    // Build instruction data
+    // Format: [discriminator(8), nonce(1), maxWeight(8), minDuration(8), maxDuration(8)]
+    let mut data = Vec::with_capacity(33);
+    data.extend_from_slice(&[145, 69, 167, 211, 154, 130, 73, 50]);
+    data.push(params.nonce);
+    data.extend_from_slice(&params.max_weight.to_le_bytes());
+    data.extend_from_slice(&params.min_duration.to_le_bytes());
+    data.extend_from_slice(&params.max_duration.to_le_bytes());
+
+    let accounts = vec![
+        AccountMeta::new(*payer, true),                         // payer
+        AccountMeta::new_readonly(*authority, false),           // authority
+        AccountMeta::new(stake_pool, false),                    // stake_pool
+        AccountMeta::new_readonly(*mint, false),                // mint
+        AccountMeta::new(stake_mint, false),                    // stake_mint
+        AccountMeta::new(vault, false),                         // vault
+        AccountMeta::new_readonly(spl_token::id(), false),      // token_program
+        AccountMeta::new_readonly(sysvar::rent::id(), false),   // rent
+        AccountMeta::new_readonly(system_program::id(), false), // system_program
+    ];
+
+    Instruction {
+        program_id: *program_id,
+        accounts,
+        data,
+    }
+}
+
+/// Build the add reward pool instruction
+///
+/// Creates the reward vault PDA for `reward_mint` and registers it in the
+/// given reward pool slot. Signed by the pool authority.
+///
+/// # Arguments
+/// * `program_id` - The staking program ID
+/// * `payer` - Account paying the reward vault's rent
+/// * `authority` - The pool authority
+/// * `stake_pool` - The stake pool
+/// * `reward_mint` - Mint of the rewards
+/// * `index` - Reward pool slot, below `MAX_REWARD_POOLS`
+///
+/// # Returns
+/// The add reward pool instruction
+pub fn build_add_reward_pool_instruction(
+    program_id: &Pubkey,
+    payer: &Pubkey,
+    authority: &Pubkey,
+    stake_pool: &Pubkey,
+    reward_mint: &Pubkey,
+    index: u8,
+) -> Instruction {
+    let (reward_vault, _) = derive_reward_vault(stake_pool, reward_mint, program_id);
+
+    // Discriminator for "add_reward_pool" instruction (from IDL), then the index
+    let data = vec![28, 53, 119, 0, 114, 211, 196, 239, index];
+
+    let accounts = vec![
+        AccountMeta::new(*payer, true),                         // payer
+        AccountMeta::new_readonly(*authority, true),            // authority
+        AccountMeta::new_readonly(*reward_mint, false),         // reward_mint
+        AccountMeta::new(*stake_pool, false),                   // stake_pool
+        AccountMeta::new(reward_vault, false),                  // reward_vault
+        AccountMeta::new_readonly(spl_token::id(), false),      // token_program
+        AccountMeta::new_readonly(sysvar::rent::id(), false),   // rent
+        AccountMeta::new_readonly(system_program::id(), false), // system_program
+    ];
+
+    Instruction {
+        program_id: *program_id,
         accounts,
         data,
     }
@@ -96,9 +440,31 @@ pub fn build_compute_budget_price_instruction(micro_lamports: u64) -> Instructio
     }
 }
 
+/// Build compute budget set compute unit limit instruction
+///
+/// # Arguments
+/// * `units` - Maximum compute units the transaction may consume
+pub fn build_compute_budget_limit_instruction(units: u32) -> Instruction {
+    let data = [2u8]
+        .iter()
+        .chain(&units.to_le_bytes())
+        .copied()
+        .collect::<Vec<u8>>();
+
+    Instruction {
+        program_id: solana_sdk::pubkey!("ComputeBudget111111111111111111111111111111"),
+        accounts: vec![],
+        data,
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::{
+        pda::derive_stake_deposit_receipt,
+        BONK_MINT, BONK_STAKE_POOL, BONK_STAKE_PROGRAM_ID,
+    };
     use std::str::FromStr;
 
     #[test]
@@ -119,4 +485,134 @@ mod tests {
         // Verify discriminator
         assert_eq!(&ix.data[0..8], &[242, 35, 198, 137, 82, 225, 242, 182]);
     }
+
+    #[test]
+    fn test_build_stake_instruction_with_reward_vaults() {
+        let user = Pubkey::from_str("6tBou5MHL5aWpDy6cgf3wiwGGK2mR8qs68ujtpaoWrf2").unwrap();
+        let second_vault = Pubkey::new_unique();
+        let reward_vaults = [BONK_REWARD_VAULT_0, second_vault];
+
+        let ix = build_stake_instruction_with_reward_vaults(&user, 1_000_000, 15_552_000, 1, &reward_vaults);
+
+        assert_eq!(ix.accounts.len(), 13); // 11 fixed + 2 reward vaults
+        assert_eq!(ix.accounts[11].pubkey, BONK_REWARD_VAULT_0);
+        assert_eq!(ix.accounts[12].pubkey, second_vault);
+    }
+
+    #[test]
+    fn test_build_stake_instruction_with_payer() {
+        let payer = Pubkey::new_unique();
+        let owner = Pubkey::new_unique();
+
+        let ix = build_stake_instruction_with_payer(&payer, &owner, 1_000_000, 15_552_000, 1, &[BONK_REWARD_VAULT_0]);
+
+        assert_eq!(ix.accounts[0].pubkey, payer);
+        assert_eq!(ix.accounts[1].pubkey, owner);
+        assert!(ix.accounts[0].is_signer && ix.accounts[1].is_signer);
+        assert_eq!(ix.accounts[2].pubkey, get_user_pool_token_ata(&owner, &PoolConfig::bonk_mainnet()));
+        assert_eq!(ix.accounts[7].pubkey, derive_stake_deposit_receipt(&owner, &BONK_STAKE_POOL, 1).0);
+    }
+
+    #[test]
+    fn test_build_stake_instruction_for_pool() {
+        let pool = PoolConfig {
+            program_id: Pubkey::new_unique(),
+            stake_pool: Pubkey::new_unique(),
+            vault: Pubkey::new_unique(),
+            mint: Pubkey::new_unique(),
+            stake_mint: Pubkey::new_unique(),
+            reward_vaults: vec![Pubkey::new_unique()],
+            decimals: 6,
+        };
+        let owner = Pubkey::new_unique();
+
+        let ix = build_stake_instruction_for_pool(&pool, &owner, &owner, 1_000_000, 15_552_000, 1);
+
+        assert_eq!(ix.program_id, pool.program_id);
+        assert_eq!(ix.accounts[2].pubkey, get_user_pool_token_ata(&owner, &pool));
+        assert_eq!(ix.accounts[3].pubkey, pool.vault);
+        assert_eq!(ix.accounts[4].pubkey, pool.stake_mint);
+        assert_eq!(ix.accounts[6].pubkey, pool.stake_pool);
+        assert_eq!(ix.accounts[7].pubkey, pool.derive_receipt(&owner, 1).0);
+        assert_eq!(ix.accounts[11].pubkey, pool.reward_vaults[0]);
+    }
+
+    #[test]
+    fn test_build_withdraw_instruction() {
+        let owner = Pubkey::from_str("6tBou5MHL5aWpDy6cgf3wiwGGK2mR8qs68ujtpaoWrf2").unwrap();
+        let destination = get_user_pool_token_ata(&owner, &PoolConfig::bonk_mainnet());
+        let from = get_user_pool_stake_ata(&owner, &PoolConfig::bonk_mainnet());
+        let reward_accounts = [(BONK_REWARD_VAULT_0, destination)];
+
+        let ix = build_withdraw_instruction(&owner, 1, &destination, &from, &reward_accounts);
+
+        assert_eq!(ix.program_id, BONK_STAKE_PROGRAM_ID);
+        assert_eq!(ix.accounts.len(), 10); // 8 fixed + 1 reward pair
+        assert!(ix.accounts[0].is_signer);
+
+        // Withdraw takes no arguments, only the discriminator
+        assert_eq!(ix.data, vec![183, 18, 70, 156, 148, 109, 161, 34]);
+    }
+
+    #[test]
+    fn test_build_claim_all_instruction() {
+        let owner = Pubkey::from_str("6tBou5MHL5aWpDy6cgf3wiwGGK2mR8qs68ujtpaoWrf2").unwrap();
+        let reward_accounts = [(BONK_REWARD_VAULT_0, get_user_pool_token_ata(&owner, &PoolConfig::bonk_mainnet()))];
+
+        let ix = build_claim_all_instruction(&owner, 1, &reward_accounts);
+
+        assert_eq!(ix.program_id, BONK_STAKE_PROGRAM_ID);
+        assert_eq!(ix.accounts.len(), 6); // 4 fixed + 1 reward pair
+        assert_eq!(ix.accounts[4].pubkey, BONK_REWARD_VAULT_0);
+        assert_eq!(ix.data, vec![194, 194, 80, 194, 234, 210, 217, 90]);
+    }
+
+    #[test]
+    fn test_build_init_stake_pool_instruction() {
+        let payer = Pubkey::new_unique();
+        let authority = Pubkey::new_unique();
+        let params = StakePoolParams {
+            nonce: 0,
+            max_weight: 4_000_000_000,
+            min_duration: 2_592_000,
+            max_duration: 31_536_000,
+        };
+
+        let ix = build_init_stake_pool_instruction(&BONK_STAKE_PROGRAM_ID, &payer, &authority, &BONK_MINT, &params);
+        let (stake_pool, _) = derive_stake_pool(0, &BONK_MINT, &authority, &BONK_STAKE_PROGRAM_ID);
+
+        assert_eq!(ix.accounts.len(), 9);
+        assert_eq!(ix.accounts[2].pubkey, stake_pool);
+        assert_eq!(ix.accounts[5].pubkey, derive_vault(&stake_pool, &BONK_STAKE_PROGRAM_ID).0);
+        assert_eq!(ix.data.len(), 33);
+        assert_eq!(&ix.data[0..8], &[145, 69, 167, 211, 154, 130, 73, 50]);
+        assert_eq!(&ix.data[9..17], &4_000_000_000u64.to_le_bytes());
+    }
+
+    #[test]
+    fn test_build_add_reward_pool_instruction() {
+        let payer = Pubkey::new_unique();
+        let authority = Pubkey::new_unique();
+
+        let ix = build_add_reward_pool_instruction(
+            &BONK_STAKE_PROGRAM_ID,
+            &payer,
+            &authority,
+            &BONK_STAKE_POOL,
+            &BONK_MINT,
+            0,
+        );
+
+        assert!(ix.accounts[1].is_signer);
+        assert_eq!(ix.accounts[4].pubkey, BONK_REWARD_VAULT_0);
+        assert_eq!(ix.data, vec![28, 53, 119, 0, 114, 211, 196, 239, 0]);
+    }
+
+    #[test]
+    fn test_build_compute_budget_limit_instruction() {
+        let ix = build_compute_budget_limit_instruction(66_000);
+
+        assert_eq!(ix.program_id, solana_sdk::pubkey!("ComputeBudget111111111111111111111111111111"));
+        assert_eq!(ix.data, vec![2, 0xd0, 0x01, 0x01, 0x00]);
+    }
 }
\ No newline at end of file
@@ -7,6 +7,15 @@
 //! ## Features
 //!
 //! - **Simple API**: Easy-to-use client for staking operations
+//! - **Async Support**: [`nonblocking::BonkStakingClient`] for async runtimes
+//! - **Any Pool**: [`PoolConfig`] points the client at any spl-token-staking pool, BONK mainnet by default
+//! - **Cluster Profiles**: Built-in mainnet and localnet profiles, plus devnet or custom pools from TOML
+//! - **Drift Checks**: Verify the configured pool addresses against the chain
+//! - **Pluggable Transport**: Run the client on any [`RpcTransport`], e.g. a fake or caching layer
+//! - **Simulation**: Preview balance changes, rent and compute units before sending
+//! - **Priority Fees**: Fixed, percentile or capped compute unit pricing
+//! - **Reliable Sending**: Rebroadcast until landed, re-sign only after provable blockhash expiry
+//! - **External Signers**: Unsigned legacy or v0 transactions for wallet adapters and remote signers
 //! - **Type-Safe**: Strongly typed interfaces for all operations
 //! - **PDA Utilities**: Helper functions for deriving program-derived addresses
 //! - **Error Handling**: Comprehensive error types with descriptive messages
@@ -14,7 +23,7 @@
 //! ## Usage
 //!
 //! ```no_run
-//! use bonk_staking_rewards::BonkStakingClient;
+//! use bonk_staking_rewards::{BonkStakingClient, LockDuration};
 //! use solana_sdk::signature::{read_keypair_file, Signer};
 //!
 //! # fn main() -> Result<(), Box<dyn std::error::Error>> {
@@ -28,7 +37,7 @@
 //!
 //! // Stake 100 BONK for 180 days
 //! let amount = 10_000_000; // 100 BONK (5 decimals)
-//! let signature = client.stake(&user, amount, 180, None)?;
+//! let signature = client.stake(&user, amount, LockDuration::SIX_MONTHS, None)?;
 //!
 //! println!("Staked! Transaction: {}", signature);
 //! # Ok(())
@@ -37,20 +46,46 @@
 
 pub mod accounts;
 pub mod client;
+pub mod cluster;
+pub mod duration;
 pub mod error;
+pub mod fees;
 pub mod instructions;
+pub mod math;
+pub mod nonblocking;
 pub mod pda;
+pub mod pool;
+pub mod program_error;
+pub mod send;
+pub mod simulation;
+pub mod transaction;
+pub mod transport;
 
 // Re-export commonly used types
-pub use client::BonkStakingClient;
+pub use client::{BonkStakingClient, RewardVaultRefresh};
+pub use cluster::ClusterProfile;
+pub use duration::LockDuration;
 pub use error::{BonkStakingError, Result};
-pub use accounts::StakeInfo;
+pub use fees::PriorityFeeStrategy;
+pub use instructions::StakePoolParams;
+pub use pool::{PoolConfig, PoolConfigDiff, PoolConfigMismatch};
+pub use program_error::ProgramErrorKind;
+pub use send::{SendConfig, SendOutcome};
+pub use simulation::TransactionPreview;
+pub use transaction::{encode_transaction_base64, TransactionVersion};
+pub use transport::RpcTransport;
+pub use accounts::{
+    PendingReward, Portfolio, RewardPool, StakeDepositReceipt, StakeInfo, StakePool,
+};
 
 use solana_sdk::pubkey::Pubkey;
 
 /// BONK token mint address
 pub const BONK_MINT: Pubkey = solana_sdk::pubkey!("DezXAZ8z7PnrnRJjz3wXBoRgixCa6xjnB7YaB1pPB263");
 
+/// BONK token decimals
+pub const BONK_DECIMALS: u8 = 5;
+
 /// BONK Stake Program ID
 pub const BONK_STAKE_PROGRAM_ID: Pubkey = solana_sdk::pubkey!("STAKEkKzbdeKkqzKpLkNQD3SUuLgshDKCD7U8duxAbB");
 
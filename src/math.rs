@@ -0,0 +1,259 @@
+//! Off-chain versions of the spl-token-staking program math
+//!
+//! These mirror the program's integer arithmetic exactly, so results match
+//! what ends up on chain.
+
+use crate::{
+    accounts::{RewardPool, StakeDepositReceipt, StakePool},
+    error::{BonkStakingError, Result},
+};
+
+/// Fixed-point scale of stake weights (a weight of 1.0x is `SCALE_FACTOR_BASE`)
+pub const SCALE_FACTOR_BASE: u64 = 1_000_000_000;
+
+/// Fixed-point scale of `rewards_per_effective_stake`
+pub const SCALE_FACTOR_BASE_SQUARED: u128 = (SCALE_FACTOR_BASE as u128) * (SCALE_FACTOR_BASE as u128);
+
+/// Calculate the stake weight for a lockup duration
+///
+/// Weight scales linearly from `base_weight` at `min_duration` to `max_weight`
+/// at `max_duration`.
+///
+/// # Returns
+/// The weight scaled by `SCALE_FACTOR_BASE`, or None if the duration is outside
+/// the pool's range or the pool parameters are invalid
+pub fn calculate_stake_weight(
+    base_weight: u64,
+    max_weight: u64,
+    min_duration: u64,
+    max_duration: u64,
+    duration: u64,
+) -> Option<u64> {
+    if duration < min_duration || duration > max_duration {
+        return None;
+    }
+
+    let duration_span = max_duration.checked_sub(min_duration)?;
+    let duration_exceeding_min = duration.checked_sub(min_duration)?;
+
+    let normalized_weight = if duration_span == 0 {
+        0
+    } else {
+        (duration_exceeding_min as u128)
+            .checked_mul(SCALE_FACTOR_BASE as u128)?
+            .checked_div(duration_span as u128)?
+    };
+    let weight_diff = max_weight.checked_sub(base_weight)? as u128;
+
+    let weight = normalized_weight
+        .checked_mul(weight_diff)?
+        .checked_div(SCALE_FACTOR_BASE as u128)?
+        .checked_add(base_weight as u128)?;
+
+    u64::try_from(weight).ok().map(|weight| weight.max(base_weight))
+}
+
+/// Calculate the effective stake of a deposit
+///
+/// # Arguments
+/// * `amount` - Amount deposited (in lamports)
+/// * `duration` - Lockup duration in seconds
+/// * `base_weight` - Pool's base weight
+/// * `max_weight` - Pool's max weight
+/// * `min_duration` - Pool's minimum lockup duration in seconds
+/// * `max_duration` - Pool's maximum lockup duration in seconds
+///
+/// # Returns
+/// The effective stake (amount times weight, scaled by `SCALE_FACTOR_BASE`)
+pub fn calculate_effective_stake(
+    amount: u64,
+    duration: u64,
+    base_weight: u64,
+    max_weight: u64,
+    min_duration: u64,
+    max_duration: u64,
+) -> Option<u128> {
+    let weight = calculate_stake_weight(base_weight, max_weight, min_duration, max_duration, duration)?;
+    (amount as u128).checked_mul(weight as u128)
+}
+
+/// Calculate the amount of stake tokens minted for an effective stake
+pub fn calculate_stake_token_amount(effective_stake: u128) -> Option<u64> {
+    u64::try_from(effective_stake / SCALE_FACTOR_BASE as u128).ok()
+}
+
+/// Calculate the stake weight for a lockup duration using a pool's parameters
+pub fn stake_weight_for_pool(stake_pool: &StakePool, duration: u64) -> Option<u64> {
+    calculate_stake_weight(
+        stake_pool.base_weight,
+        stake_pool.max_weight,
+        stake_pool.min_duration,
+        stake_pool.max_duration,
+        duration,
+    )
+}
+
+/// Calculate the effective stake of a deposit using a pool's parameters
+pub fn effective_stake_for_pool(stake_pool: &StakePool, amount: u64, duration: u64) -> Option<u128> {
+    calculate_effective_stake(
+        amount,
+        duration,
+        stake_pool.base_weight,
+        stake_pool.max_weight,
+        stake_pool.min_duration,
+        stake_pool.max_duration,
+    )
+}
+
+/// Check a receipt's effective stake against the pool's weight formula
+///
+/// Only meaningful while the pool's weights and durations are the ones that
+/// were in effect at deposit time.
+pub fn check_effective_stake(receipt: &StakeDepositReceipt, stake_pool: &StakePool) -> Result<()> {
+    let expected = effective_stake_for_pool(stake_pool, receipt.deposit_amount, receipt.lockup_duration)
+        .ok_or_else(|| {
+            BonkStakingError::InvalidDuration(format!(
+                "Lockup of {} seconds is outside the pool's range",
+                receipt.lockup_duration
+            ))
+        })?;
+
+    if expected != receipt.effective_stake {
+        return Err(BonkStakingError::InvalidAccountData(format!(
+            "Effective stake mismatch: receipt has {}, expected {}",
+            receipt.effective_stake, expected
+        )));
+    }
+
+    Ok(())
+}
+
+/// Bring a reward pool's `rewards_per_effective_stake` up to date
+///
+/// Rewards sent to the vault since the program last synced it (vault balance
+/// above `last_amount`) are spread over the pool's total weighted stake, the
+/// same way the program does on its next instruction.
+///
+/// # Arguments
+/// * `reward_pool` - The reward pool as stored in the stake pool
+/// * `vault_balance` - Current balance of the reward vault
+/// * `total_weighted_stake` - The stake pool's total weighted stake
+pub fn synced_rewards_per_effective_stake(
+    reward_pool: &RewardPool,
+    vault_balance: u64,
+    total_weighted_stake: u128,
+) -> Option<u128> {
+    if total_weighted_stake == 0 || vault_balance <= reward_pool.last_amount {
+        return Some(reward_pool.rewards_per_effective_stake);
+    }
+
+    let new_rewards = (vault_balance - reward_pool.last_amount) as u128;
+    let increase = new_rewards
+        .checked_mul(SCALE_FACTOR_BASE_SQUARED)?
+        .checked_div(total_weighted_stake)?;
+
+    reward_pool.rewards_per_effective_stake.checked_add(increase)
+}
+
+/// Calculate the rewards claimable from one reward pool
+///
+/// # Arguments
+/// * `rewards_per_effective_stake` - The reward pool's current (synced) value
+/// * `claimed_amount` - The receipt's `claimed_amounts` entry for this pool
+/// * `effective_stake` - The receipt's effective stake
+pub fn calculate_claimable_rewards(
+    rewards_per_effective_stake: u128,
+    claimed_amount: u128,
+    effective_stake: u128,
+) -> Option<u64> {
+    let amount = rewards_per_effective_stake
+        .checked_sub(claimed_amount)?
+        .checked_mul(effective_stake)?
+        .checked_div(SCALE_FACTOR_BASE_SQUARED)?;
+
+    u64::try_from(amount).ok()
+}
+
+/// Calculate the pending rewards of a receipt for every initialized reward pool
+///
+/// # Arguments
+/// * `stake_pool` - The stake pool the receipt belongs to
+/// * `receipt` - The stake deposit receipt
+/// * `vault_balances` - Current reward vault balances, in the order of
+///   `stake_pool.initialized_reward_pools()`
+///
+/// # Returns
+/// Claimable amounts in the order of `stake_pool.initialized_reward_pools()`
+pub fn calculate_pending_rewards(
+    stake_pool: &StakePool,
+    receipt: &StakeDepositReceipt,
+    vault_balances: &[u64],
+) -> Option<Vec<u64>> {
+    stake_pool
+        .initialized_reward_pools()
+        .zip(vault_balances)
+        .map(|((index, reward_pool), vault_balance)| {
+            let rewards_per_effective_stake = synced_rewards_per_effective_stake(
+                reward_pool,
+                *vault_balance,
+                stake_pool.total_weighted_stake,
+            )?;
+            calculate_claimable_rewards(
+                rewards_per_effective_stake,
+                receipt.claimed_amounts[index],
+                receipt.effective_stake,
+            )
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const BASE_WEIGHT: u64 = SCALE_FACTOR_BASE;
+    const MAX_WEIGHT: u64 = 4 * SCALE_FACTOR_BASE;
+    const MIN_DURATION: u64 = 2_592_000; // 30 days
+    const MAX_DURATION: u64 = 31_536_000; // 365 days
+
+    #[test]
+    fn test_stake_weight_bounds() {
+        let weight = |duration| calculate_stake_weight(BASE_WEIGHT, MAX_WEIGHT, MIN_DURATION, MAX_DURATION, duration);
+
+        assert_eq!(weight(MIN_DURATION), Some(BASE_WEIGHT));
+        assert_eq!(weight(MAX_DURATION), Some(MAX_WEIGHT));
+        assert_eq!(weight(MIN_DURATION - 1), None);
+        assert_eq!(weight(MAX_DURATION + 1), None);
+    }
+
+    #[test]
+    fn test_stake_weight_is_linear_with_truncation() {
+        // 180 days: (15_552_000 - 2_592_000) * 1e9 / 28_944_000 = 447_761_194 (truncated)
+        let weight = calculate_stake_weight(BASE_WEIGHT, MAX_WEIGHT, MIN_DURATION, MAX_DURATION, 15_552_000);
+        assert_eq!(weight, Some(BASE_WEIGHT + 447_761_194 * 3));
+    }
+
+    #[test]
+    fn test_pending_rewards_include_unsynced_vault_balance() {
+        let mut reward_pool = RewardPool::default();
+        reward_pool.reward_vault = crate::BONK_REWARD_VAULT_0;
+        reward_pool.rewards_per_effective_stake = 5 * SCALE_FACTOR_BASE as u128;
+        reward_pool.last_amount = 1_000;
+
+        // 500 new reward tokens over 1e12 effective stake adds 5e8 per effective stake
+        let synced = synced_rewards_per_effective_stake(&reward_pool, 1_500, 1_000_000_000_000);
+        assert_eq!(synced, Some(5 * SCALE_FACTOR_BASE as u128 + 500_000_000));
+
+        // A position holding half the weighted stake, last claimed at 4e9
+        let claimable = calculate_claimable_rewards(synced.unwrap(), 4 * SCALE_FACTOR_BASE as u128, 500_000_000_000);
+        assert_eq!(claimable, Some(750));
+    }
+
+    #[test]
+    fn test_effective_stake() {
+        let effective = calculate_effective_stake(10_000_000, MAX_DURATION, BASE_WEIGHT, MAX_WEIGHT, MIN_DURATION, MAX_DURATION);
+
+        assert_eq!(effective, Some(10_000_000 * 4 * SCALE_FACTOR_BASE as u128));
+        assert_eq!(calculate_stake_token_amount(effective.unwrap()), Some(40_000_000));
+    }
+}
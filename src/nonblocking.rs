@@ -0,0 +1,378 @@
+//! Async client for BONK staking operations
+//!
+//! Mirrors [`crate::client::BonkStakingClient`] on top of the nonblocking
+//! `RpcClient`, sharing its instruction building and account decoding.
+
+use std::{collections::HashSet, path::Path};
+
+use solana_client::nonblocking::rpc_client::RpcClient;
+use solana_sdk::{
+    account::Account,
+    commitment_config::CommitmentConfig,
+    instruction::Instruction,
+    pubkey::Pubkey,
+    signature::Signature,
+    signer::Signer,
+    transaction::Transaction,
+};
+
+use crate::{
+    accounts::{get_user_pool_stake_ata, get_user_pool_token_ata, StakeInfo, StakePool},
+    client::{
+        candidate_receipts, is_gpa_unsupported, next_nonce_from_receipt_accounts, next_nonce_from_scan,
+        read_token_balance, receipt_accounts_config, stake_instructions, stakes_from_receipt_accounts,
+        stakes_from_scan, MAX_MULTIPLE_ACCOUNTS,
+    },
+    cluster::ClusterProfile,
+    duration::LockDuration,
+    error::{BonkStakingError, Result},
+    fees::{pool_writable_accounts, PriorityFeeStrategy},
+    pda::ReceiptDeriver,
+    pool::PoolConfig,
+    program_error::send_error,
+};
+
+/// Async client for BONK staking operations
+///
+/// Unlike the blocking client, the stake pool is read fresh for every stake
+/// rather than cached.
+pub struct BonkStakingClient {
+    /// Nonblocking RPC client for communicating with Solana
+    rpc: RpcClient,
+    /// Addresses of the stake pool the client works with
+    pool: PoolConfig,
+    /// Memoized receipt addresses in the pool
+    receipts: ReceiptDeriver,
+    /// How the compute unit price is chosen
+    priority_fee: PriorityFeeStrategy,
+}
+
+impl BonkStakingClient {
+    /// Create a new async BonkStakingClient
+    ///
+    /// # Arguments
+    /// * `rpc_url` - The Solana RPC endpoint URL
+    ///
+    /// # Example
+    /// ```
+    /// use bonk_staking_rewards::nonblocking::BonkStakingClient;
+    ///
+    /// let client = BonkStakingClient::new(
+    ///     "https://mainnet.helius-rpc.com/?api-key=YOUR_KEY".to_string()
+    /// );
+    /// ```
+    pub fn new(rpc_url: String) -> Self {
+        let rpc = RpcClient::new_with_commitment(rpc_url, CommitmentConfig::confirmed());
+        Self {
+            rpc,
+            pool: PoolConfig::default(),
+            receipts: ReceiptDeriver::for_pool(&PoolConfig::default()),
+            priority_fee: PriorityFeeStrategy::default(),
+        }
+    }
+
+    /// Create a client from a built-in cluster profile
+    ///
+    /// # Arguments
+    /// * `name` - `mainnet` or `localnet`, see [`Self::from_profile_file`] for others
+    pub fn from_profile(name: &str) -> Result<Self> {
+        Ok(Self::from_cluster_profile(&ClusterProfile::find(name, None)?))
+    }
+
+    /// Create a client from a profile in a TOML profile file, or a built-in one
+    ///
+    /// # Arguments
+    /// * `path` - TOML profile file, see [`crate::cluster`]
+    /// * `name` - Profile name
+    pub fn from_profile_file(path: impl AsRef<Path>, name: &str) -> Result<Self> {
+        Ok(Self::from_cluster_profile(&ClusterProfile::find(name, Some(path.as_ref()))?))
+    }
+
+    /// Create a client for a cluster profile's RPC endpoint and pool
+    pub fn from_cluster_profile(profile: &ClusterProfile) -> Self {
+        Self::new(profile.rpc_url.clone()).with_pool(profile.pool.clone())
+    }
+
+    /// Set the stake pool the client works with
+    ///
+    /// Defaults to the BONK pool on mainnet.
+    pub fn with_pool(mut self, pool: PoolConfig) -> Self {
+        self.receipts = ReceiptDeriver::for_pool(&pool);
+        self.pool = pool;
+        self
+    }
+
+    /// Get the configured stake pool
+    pub fn pool_config(&self) -> &PoolConfig {
+        &self.pool
+    }
+
+    /// Set how the compute unit price is chosen
+    ///
+    /// Defaults to a fixed price of [`crate::fees::DEFAULT_PRIORITY_FEE`] micro-lamports.
+    pub fn with_priority_fee(mut self, strategy: PriorityFeeStrategy) -> Self {
+        self.priority_fee = strategy;
+        self
+    }
+
+    /// Stake tokens in the configured pool
+    ///
+    /// # Arguments
+    /// * `user` - Signer owning the tokens and paying fees, e.g. a `Keypair`
+    /// * `amount` - Amount of tokens to stake (in base units, not UI amount)
+    /// * `lock_duration` - Lock duration, within the pool's min/max duration
+    /// * `nonce` - Nonce for the stake deposit receipt (use None for auto-select)
+    ///
+    /// # Returns
+    /// Transaction signature
+    ///
+    /// # Example
+    /// ```no_run
+    /// use bonk_staking_rewards::{nonblocking::BonkStakingClient, LockDuration};
+    /// use solana_sdk::signature::Keypair;
+    ///
+    /// # async fn run() -> bonk_staking_rewards::Result<()> {
+    /// let client = BonkStakingClient::new("https://api.mainnet-beta.solana.com".to_string());
+    /// let user = Keypair::new();
+    /// let amount = 10_000_000; // 100 BONK (5 decimals)
+    /// let signature = client.stake(&user, amount, LockDuration::SIX_MONTHS, None).await?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn stake(
+        &self,
+        user: &dyn Signer,
+        amount: u64,
+        lock_duration: LockDuration,
+        nonce: Option<u32>,
+    ) -> Result<Signature> {
+        self.stake_with_payer(user, user, amount, lock_duration, nonce).await
+    }
+
+    /// Stake tokens with a separate fee and rent payer
+    ///
+    /// The payer covers the transaction fee and the rent of any new accounts,
+    /// while the owner's tokens are staked and the position belongs to the owner.
+    ///
+    /// # Arguments
+    /// * `payer` - Signer paying fees and rent, e.g. a treasury hot wallet
+    /// * `owner` - Signer owning the tokens and the position, e.g. a remote or
+    ///   multisig signer
+    /// * `amount` - Amount of tokens to stake (in base units, not UI amount)
+    /// * `lock_duration` - Lock duration, within the pool's min/max duration
+    /// * `nonce` - Nonce for the stake deposit receipt (use None for auto-select)
+    ///
+    /// # Returns
+    /// Transaction signature
+    pub async fn stake_with_payer(
+        &self,
+        payer: &dyn Signer,
+        owner: &dyn Signer,
+        amount: u64,
+        lock_duration: LockDuration,
+        nonce: Option<u32>,
+    ) -> Result<Signature> {
+        let owner_pubkey = owner.pubkey();
+        // Validate amount
+        if amount == 0 {
+            return Err(BonkStakingError::InvalidAmount(
+                "Amount must be greater than 0".to_string(),
+            ));
+        }
+
+        // Validate duration against the pool's lockup bounds
+        let stake_pool = self.get_stake_pool().await?;
+        let lock_duration_seconds = lock_duration.validate(&stake_pool)?;
+
+        // Get or auto-select nonce
+        let stake_nonce = match nonce {
+            Some(n) => n,
+            None => self.find_next_available_nonce(&owner_pubkey).await?,
+        };
+
+        // Check BONK balance
+        let token_balance = self.get_token_balance(&owner_pubkey).await?;
+        if token_balance < amount {
+            return Err(BonkStakingError::InsufficientBalance {
+                required: amount,
+                available: token_balance,
+            });
+        }
+
+        // Build instructions
+        let reward_vaults: Vec<Pubkey> = stake_pool
+            .initialized_reward_pools()
+            .map(|(_, pool)| pool.reward_vault)
+            .collect();
+        let live_pool = self.pool.with_reward_vaults(reward_vaults);
+        let micro_lamports = self.compute_unit_price(&live_pool).await?;
+        let instructions = stake_instructions(
+            &live_pool,
+            &payer.pubkey(),
+            &owner_pubkey,
+            amount,
+            lock_duration_seconds,
+            stake_nonce,
+            micro_lamports,
+        );
+
+        // Send transaction
+        self.send_transaction(&instructions, payer, owner).await
+    }
+
+    /// Get user's balance of the pool's token
+    ///
+    /// # Arguments
+    /// * `user` - The user's public key
+    ///
+    /// # Returns
+    /// Balance in the mint's smallest unit
+    pub async fn get_token_balance(&self, user: &Pubkey) -> Result<u64> {
+        let token_ata = get_user_pool_token_ata(user, &self.pool);
+
+        // The ATA doesn't exist until the first transfer in, that's a zero balance
+        read_token_balance(self.get_account(&token_ata).await?.as_ref())
+    }
+
+    /// Get user's balance of the pool's token
+    #[deprecated(note = "reads the configured pool's mint, use `get_token_balance`")]
+    pub async fn get_bonk_balance(&self, user: &Pubkey) -> Result<u64> {
+        self.get_token_balance(user).await
+    }
+
+    /// Get user's stake token balance
+    ///
+    /// # Arguments
+    /// * `user` - The user's public key
+    ///
+    /// # Returns
+    /// Stake token balance in lamports
+    pub async fn get_stake_balance(&self, user: &Pubkey) -> Result<u64> {
+        let stake_ata = get_user_pool_stake_ata(user, &self.pool);
+
+        // The ATA doesn't exist until the first transfer in, that's a zero balance
+        read_token_balance(self.get_account(&stake_ata).await?.as_ref())
+    }
+
+    /// Get the configured stake pool
+    ///
+    /// # Returns
+    /// The decoded stake pool, including its initialized reward pools
+    pub async fn get_stake_pool(&self) -> Result<StakePool> {
+        let account = self
+            .get_account(&self.pool.stake_pool)
+            .await?
+            .ok_or_else(|| BonkStakingError::AccountNotFound(self.pool.stake_pool.to_string()))?;
+
+        StakePool::try_deserialize(&account.data)
+    }
+
+    /// Get user's active stakes
+    ///
+    /// Fetches every stake deposit receipt owned by the user with a single
+    /// `getProgramAccounts` call. If the RPC doesn't support it, scans nonces
+    /// 0-99 instead; other RPC errors are returned as is.
+    ///
+    /// # Arguments
+    /// * `user` - The user's public key
+    ///
+    /// # Returns
+    /// Vector of active stakes, ordered by nonce
+    pub async fn get_user_stakes(&self, user: &Pubkey) -> Result<Vec<StakeInfo>> {
+        match self.find_receipt_accounts(user).await {
+            Ok(receipts) => stakes_from_receipt_accounts(&self.receipts, user, receipts),
+            Err(err) if is_gpa_unsupported(&err) => {
+                log::debug!("getProgramAccounts unsupported, scanning nonces instead: {}", err);
+                let accounts = self.get_accounts(&candidate_receipts(&self.receipts, user)).await?;
+                stakes_from_scan(&self.receipts, user, accounts)
+            }
+            Err(err) => Err(err),
+        }
+    }
+
+    /// Find the next available nonce for a user
+    async fn find_next_available_nonce(&self, user: &Pubkey) -> Result<u32> {
+        match self.find_receipt_accounts(user).await {
+            Ok(receipts) => next_nonce_from_receipt_accounts(&self.receipts, user, &receipts, &HashSet::new()),
+            Err(err) if is_gpa_unsupported(&err) => {
+                log::debug!("getProgramAccounts unsupported, scanning nonces instead: {}", err);
+                let accounts = self.get_accounts(&candidate_receipts(&self.receipts, user)).await?;
+                next_nonce_from_scan(&accounts, &HashSet::new())
+            }
+            Err(err) => Err(err),
+        }
+    }
+
+    /// Fetch all of a user's stake deposit receipt accounts in the pool
+    async fn find_receipt_accounts(&self, user: &Pubkey) -> Result<Vec<(Pubkey, Account)>> {
+        Ok(self
+            .rpc
+            .get_program_accounts_with_config(&self.pool.program_id, receipt_accounts_config(&self.pool, user))
+            .await?)
+    }
+
+    /// Fetch an account, None if it doesn't exist
+    async fn get_account(&self, address: &Pubkey) -> Result<Option<Account>> {
+        Ok(self
+            .rpc
+            .get_account_with_commitment(address, self.rpc.commitment())
+            .await?
+            .value)
+    }
+
+    /// Fetch accounts with `getMultipleAccounts`, in chunks of 100
+    async fn get_accounts(&self, addresses: &[Pubkey]) -> Result<Vec<Option<Account>>> {
+        let mut accounts = Vec::with_capacity(addresses.len());
+        for chunk in addresses.chunks(MAX_MULTIPLE_ACCOUNTS) {
+            accounts.extend(self.rpc.get_multiple_accounts(chunk).await?);
+        }
+
+        Ok(accounts)
+    }
+
+    /// Pick the compute unit price with the configured priority fee strategy
+    async fn compute_unit_price(&self, pool: &PoolConfig) -> Result<u64> {
+        if !self.priority_fee.needs_recent_fees() {
+            return Ok(self.priority_fee.micro_lamports(&[]));
+        }
+
+        let recent_fees: Vec<u64> = self
+            .rpc
+            .get_recent_prioritization_fees(&pool_writable_accounts(pool))
+            .await?
+            .iter()
+            .map(|fee| fee.prioritization_fee)
+            .collect();
+
+        Ok(self.priority_fee.micro_lamports(&recent_fees))
+    }
+
+    /// Send a transaction with the given instructions, signed by the payer and owner
+    async fn send_transaction(
+        &self,
+        instructions: &[Instruction],
+        payer: &dyn Signer,
+        owner: &dyn Signer,
+    ) -> Result<Signature> {
+        let recent_blockhash = self.rpc.get_latest_blockhash().await?;
+
+        let mut signers = vec![payer];
+        if owner.pubkey() != payer.pubkey() {
+            signers.push(owner);
+        }
+        let transaction = Transaction::new_signed_with_payer(
+            instructions,
+            Some(&payer.pubkey()),
+            &signers,
+            recent_blockhash,
+        );
+
+        let signature = self
+            .rpc
+            .send_and_confirm_transaction(&transaction)
+            .await
+            .map_err(|e| send_error(e.into(), &self.pool.program_id, instructions))?;
+
+        Ok(signature)
+    }
+}
@@ -1,8 +1,29 @@
 //! PDA (Program Derived Address) utilities for BONK staking
 
+use std::{collections::HashMap, sync::Mutex};
+
 use solana_sdk::pubkey::Pubkey;
 
-use crate::BONK_STAKE_PROGRAM_ID;
+use crate::{
+    error::{BonkStakingError, Result},
+    pool::PoolConfig,
+    BONK_STAKE_PROGRAM_ID,
+};
+
+/// Bump of the BONK pool's vault PDA
+pub const BONK_VAULT_BUMP: u8 = 254;
+
+/// Bump of the BONK pool's stake mint PDA
+pub const BONK_STAKE_MINT_BUMP: u8 = 254;
+
+/// Bump of the BONK pool's first reward vault PDA
+pub const BONK_REWARD_VAULT_0_BUMP: u8 = 255;
+
+/// Most owners a [`ReceiptDeriver`] keeps receipts for, evicting the least recently used
+const MAX_CACHED_OWNERS: usize = 16;
+
+/// Most receipts kept per owner, above the 10,000 nonces of one nonce recovery
+const MAX_CACHED_RECEIPTS_PER_OWNER: usize = 16_384;
 
 /// Derive the stake deposit receipt PDA
 ///
@@ -23,6 +44,25 @@ pub fn derive_stake_deposit_receipt(
     owner: &Pubkey,
     stake_pool: &Pubkey,
     nonce: u32,
+) -> (Pubkey, u8) {
+    derive_stake_deposit_receipt_with_program_id(&BONK_STAKE_PROGRAM_ID, owner, stake_pool, nonce)
+}
+
+/// Derive the stake deposit receipt PDA for a deployment of the staking program
+///
+/// # Arguments
+/// * `program_id` - The staking program ID
+/// * `owner` - The owner/user public key
+/// * `stake_pool` - The stake pool public key
+/// * `nonce` - The nonce for this stake
+///
+/// # Returns
+/// A tuple of (PDA address, bump seed)
+pub fn derive_stake_deposit_receipt_with_program_id(
+    program_id: &Pubkey,
+    owner: &Pubkey,
+    stake_pool: &Pubkey,
+    nonce: u32,
 ) -> (Pubkey, u8) {
     Pubkey::find_program_address(
         &[
@@ -31,13 +71,204 @@ pub fn derive_stake_deposit_receipt(
             &nonce.to_le_bytes(),
             b"stakeDepositReceipt", // camelCase, not snake_case!
         ],
-        &BONK_STAKE_PROGRAM_ID,
+        program_id,
     )
 }
 
+/// Derive the stake pool PDA
+///
+/// Seeds: nonce (u8), staked mint, pool authority, "stakePool"
+///
+/// # Arguments
+/// * `nonce` - Nonce distinguishing pools with the same mint and authority
+/// * `mint` - The staked token mint
+/// * `authority` - The pool authority
+/// * `program_id` - The staking program ID
+///
+/// # Returns
+/// A tuple of (PDA address, bump seed)
+pub fn derive_stake_pool(nonce: u8, mint: &Pubkey, authority: &Pubkey, program_id: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(
+        &[&[nonce], mint.as_ref(), authority.as_ref(), b"stakePool"],
+        program_id,
+    )
+}
+
+/// Derive the vault PDA holding a stake pool's staked tokens
+///
+/// # Arguments
+/// * `stake_pool` - The stake pool public key
+/// * `program_id` - The staking program ID
+///
+/// # Returns
+/// A tuple of (PDA address, bump seed)
+pub fn derive_vault(stake_pool: &Pubkey, program_id: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[stake_pool.as_ref(), b"vault"], program_id)
+}
+
+/// Derive the stake mint PDA of a stake pool
+///
+/// # Arguments
+/// * `stake_pool` - The stake pool public key
+/// * `program_id` - The staking program ID
+///
+/// # Returns
+/// A tuple of (PDA address, bump seed)
+pub fn derive_stake_mint(stake_pool: &Pubkey, program_id: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[stake_pool.as_ref(), b"stakeMint"], program_id)
+}
+
+/// Derive the reward vault PDA of a stake pool's reward pool
+///
+/// # Arguments
+/// * `stake_pool` - The stake pool public key
+/// * `reward_mint` - Mint of the rewards paid out by the reward pool
+/// * `program_id` - The staking program ID
+///
+/// # Returns
+/// A tuple of (PDA address, bump seed)
+pub fn derive_reward_vault(stake_pool: &Pubkey, reward_mint: &Pubkey, program_id: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(
+        &[stake_pool.as_ref(), reward_mint.as_ref(), b"rewardVault"],
+        program_id,
+    )
+}
+
+/// Create the stake pool address from a known bump (`StakePool.bump_seed`)
+pub fn create_stake_pool_address(
+    nonce: u8,
+    mint: &Pubkey,
+    authority: &Pubkey,
+    bump: u8,
+    program_id: &Pubkey,
+) -> Result<Pubkey> {
+    create_address(
+        &[&[nonce], mint.as_ref(), authority.as_ref(), b"stakePool", &[bump]],
+        program_id,
+    )
+}
+
+/// Create the vault address from a known bump
+pub fn create_vault_address(stake_pool: &Pubkey, bump: u8, program_id: &Pubkey) -> Result<Pubkey> {
+    create_address(&[stake_pool.as_ref(), b"vault", &[bump]], program_id)
+}
+
+/// Create the stake mint address from a known bump
+pub fn create_stake_mint_address(stake_pool: &Pubkey, bump: u8, program_id: &Pubkey) -> Result<Pubkey> {
+    create_address(&[stake_pool.as_ref(), b"stakeMint", &[bump]], program_id)
+}
+
+/// Create a reward vault address from a known bump
+pub fn create_reward_vault_address(
+    stake_pool: &Pubkey,
+    reward_mint: &Pubkey,
+    bump: u8,
+    program_id: &Pubkey,
+) -> Result<Pubkey> {
+    create_address(
+        &[stake_pool.as_ref(), reward_mint.as_ref(), b"rewardVault", &[bump]],
+        program_id,
+    )
+}
+
+fn create_address(seeds: &[&[u8]], program_id: &Pubkey) -> Result<Pubkey> {
+    Pubkey::create_program_address(seeds, program_id)
+        .map_err(|e| BonkStakingError::PdaDerivationError(e.to_string()))
+}
+
+/// Memoizing stake deposit receipt deriver for one pool
+///
+/// Nonce scans and nonce recovery derive the same owner/nonce receipts on
+/// every query; this derives each one once. Receipts are cached per owner,
+/// and the least recently used owner is evicted when the cache is full.
+#[derive(Debug)]
+pub struct ReceiptDeriver {
+    program_id: Pubkey,
+    stake_pool: Pubkey,
+    cache: Mutex<ReceiptCache>,
+}
+
+/// Cached receipts of the most recently used owners
+#[derive(Debug, Default)]
+struct ReceiptCache {
+    owners: HashMap<Pubkey, OwnerReceipts>,
+    /// Incremented on every lookup, to order owners by last use
+    tick: u64,
+}
+
+/// One owner's cached receipts, by nonce
+#[derive(Debug, Default)]
+struct OwnerReceipts {
+    last_used: u64,
+    receipts: HashMap<u32, (Pubkey, u8)>,
+}
+
+impl ReceiptCache {
+    /// Look up a receipt, marking its owner as used
+    fn get(&mut self, owner: &Pubkey, nonce: u32) -> Option<(Pubkey, u8)> {
+        self.tick += 1;
+        let owner_receipts = self.owners.get_mut(owner)?;
+        owner_receipts.last_used = self.tick;
+        owner_receipts.receipts.get(&nonce).copied()
+    }
+
+    /// Cache a receipt, evicting the least recently used owner when full
+    fn insert(&mut self, owner: &Pubkey, nonce: u32, receipt: (Pubkey, u8)) {
+        if !self.owners.contains_key(owner) && self.owners.len() >= MAX_CACHED_OWNERS {
+            let least_recent = self
+                .owners
+                .iter()
+                .min_by_key(|(_, owner_receipts)| owner_receipts.last_used)
+                .map(|(owner, _)| *owner);
+            if let Some(least_recent) = least_recent {
+                self.owners.remove(&least_recent);
+            }
+        }
+
+        let owner_receipts = self.owners.entry(*owner).or_default();
+        if owner_receipts.receipts.len() >= MAX_CACHED_RECEIPTS_PER_OWNER {
+            owner_receipts.receipts.clear();
+        }
+        owner_receipts.last_used = self.tick;
+        owner_receipts.receipts.insert(nonce, receipt);
+    }
+}
+
+impl ReceiptDeriver {
+    /// Create a deriver for a pool of the given staking program
+    pub fn new(program_id: Pubkey, stake_pool: Pubkey) -> Self {
+        Self {
+            program_id,
+            stake_pool,
+            cache: Mutex::new(ReceiptCache::default()),
+        }
+    }
+
+    /// Create a deriver for a configured pool
+    pub fn for_pool(pool: &PoolConfig) -> Self {
+        Self::new(pool.program_id, pool.stake_pool)
+    }
+
+    /// Derive a receipt, from the cache when it was derived before
+    ///
+    /// # Returns
+    /// A tuple of (PDA address, bump seed)
+    pub fn derive(&self, owner: &Pubkey, nonce: u32) -> (Pubkey, u8) {
+        if let Some(receipt) = self.cache.lock().unwrap().get(owner, nonce) {
+            return receipt;
+        }
+
+        let receipt = derive_stake_deposit_receipt_with_program_id(&self.program_id, owner, &self.stake_pool, nonce);
+        self.cache.lock().unwrap().insert(owner, nonce, receipt);
+
+        receipt
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::{BONK_MINT, BONK_REWARD_VAULT_0, BONK_STAKE_MINT, BONK_STAKE_POOL, BONK_VAULT};
     use std::str::FromStr;
 
     #[test]
@@ -58,4 +289,62 @@ mod tests {
             "Do2sHbcqswaLupdvjGiTZHh4U9GB3xF3HztsZoeLBmHh"
         );
     }
+
+    #[test]
+    fn test_known_bump_fast_paths() {
+        let program_id = BONK_STAKE_PROGRAM_ID;
+
+        assert_eq!(create_vault_address(&BONK_STAKE_POOL, BONK_VAULT_BUMP, &program_id).unwrap(), BONK_VAULT);
+        assert_eq!(
+            create_stake_mint_address(&BONK_STAKE_POOL, BONK_STAKE_MINT_BUMP, &program_id).unwrap(),
+            BONK_STAKE_MINT
+        );
+        assert_eq!(
+            create_reward_vault_address(&BONK_STAKE_POOL, &BONK_MINT, BONK_REWARD_VAULT_0_BUMP, &program_id).unwrap(),
+            BONK_REWARD_VAULT_0
+        );
+    }
+
+    #[test]
+    fn test_receipt_deriver() {
+        let owner = Pubkey::new_unique();
+        let deriver = ReceiptDeriver::for_pool(&PoolConfig::bonk_mainnet());
+
+        let expected = derive_stake_deposit_receipt(&owner, &BONK_STAKE_POOL, 3);
+        assert_eq!(deriver.derive(&owner, 3), expected);
+        assert_eq!(deriver.derive(&owner, 3), expected);
+        assert_eq!(deriver.cache.lock().unwrap().owners[&owner].receipts.len(), 1);
+    }
+
+    #[test]
+    fn test_receipt_deriver_evicts_least_recent_owner() {
+        let deriver = ReceiptDeriver::for_pool(&PoolConfig::bonk_mainnet());
+        let owners: Vec<Pubkey> = (0..MAX_CACHED_OWNERS).map(|_| Pubkey::new_unique()).collect();
+        for owner in &owners {
+            deriver.derive(owner, 0);
+            deriver.derive(owner, 1);
+        }
+
+        // Using the first owner again makes the second the least recent
+        deriver.derive(&owners[0], 0);
+        let newcomer = Pubkey::new_unique();
+        assert_eq!(deriver.derive(&newcomer, 0), derive_stake_deposit_receipt(&newcomer, &BONK_STAKE_POOL, 0));
+
+        let cache = deriver.cache.lock().unwrap();
+        assert_eq!(cache.owners.len(), MAX_CACHED_OWNERS);
+        assert!(!cache.owners.contains_key(&owners[1]));
+        assert_eq!(cache.owners[&owners[0]].receipts.len(), 2);
+        assert_eq!(cache.owners[&owners[2]].receipts.len(), 2);
+        assert!(cache.owners.contains_key(&newcomer));
+    }
+
+    #[test]
+    fn test_pool_pda_derivation() {
+        assert_eq!(derive_vault(&BONK_STAKE_POOL, &BONK_STAKE_PROGRAM_ID).0, BONK_VAULT);
+        assert_eq!(derive_stake_mint(&BONK_STAKE_POOL, &BONK_STAKE_PROGRAM_ID).0, BONK_STAKE_MINT);
+        assert_eq!(
+            derive_reward_vault(&BONK_STAKE_POOL, &BONK_MINT, &BONK_STAKE_PROGRAM_ID).0,
+            BONK_REWARD_VAULT_0
+        );
+    }
 }
\ No newline at end of file
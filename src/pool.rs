@@ -0,0 +1,389 @@
+//! Stake pool configuration
+//!
+//! The spl-token-staking program hosts many pools besides BONK's. A
+//! [`PoolConfig`] holds the addresses of one of them, so the client and the
+//! instruction builders can work with any pool of the program.
+
+use std::fmt;
+
+use solana_sdk::pubkey::Pubkey;
+
+use crate::{
+    accounts::StakePool, pda::derive_stake_deposit_receipt_with_program_id, BONK_DECIMALS,
+    BONK_MINT, BONK_REWARD_VAULT_0, BONK_STAKE_MINT, BONK_STAKE_POOL, BONK_STAKE_PROGRAM_ID,
+    BONK_VAULT,
+};
+
+/// Addresses of a stake pool of the spl-token-staking program
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PoolConfig {
+    /// The staking program
+    pub program_id: Pubkey,
+    /// The stake pool account
+    pub stake_pool: Pubkey,
+    /// Token account holding the staked tokens
+    pub vault: Pubkey,
+    /// Mint of the staked token
+    pub mint: Pubkey,
+    /// Mint of the stake tokens given to depositors
+    pub stake_mint: Pubkey,
+    /// Vaults of the initialized reward pools, in slot order
+    pub reward_vaults: Vec<Pubkey>,
+    /// Decimals of the staked token
+    pub decimals: u8,
+}
+
+impl Default for PoolConfig {
+    fn default() -> Self {
+        Self::bonk_mainnet()
+    }
+}
+
+impl PoolConfig {
+    /// The BONK stake pool on mainnet
+    pub fn bonk_mainnet() -> Self {
+        Self {
+            program_id: BONK_STAKE_PROGRAM_ID,
+            stake_pool: BONK_STAKE_POOL,
+            vault: BONK_VAULT,
+            mint: BONK_MINT,
+            stake_mint: BONK_STAKE_MINT,
+            reward_vaults: vec![BONK_REWARD_VAULT_0],
+            decimals: BONK_DECIMALS,
+        }
+    }
+
+    /// Build a config from decoded on-chain stake pool data
+    ///
+    /// # Arguments
+    /// * `program_id` - The staking program owning the pool
+    /// * `stake_pool_address` - Address of the stake pool account
+    /// * `stake_pool` - The decoded stake pool
+    /// * `decimals` - Decimals of the pool's mint
+    pub fn from_stake_pool(
+        program_id: Pubkey,
+        stake_pool_address: Pubkey,
+        stake_pool: &StakePool,
+        decimals: u8,
+    ) -> Self {
+        Self {
+            program_id,
+            stake_pool: stake_pool_address,
+            vault: stake_pool.vault,
+            mint: stake_pool.mint,
+            stake_mint: stake_pool.stake_mint,
+            reward_vaults: stake_pool
+                .initialized_reward_pools()
+                .map(|(_, reward_pool)| reward_pool.reward_vault)
+                .collect(),
+            decimals,
+        }
+    }
+
+    /// Copy of this config with a different reward vault list
+    pub fn with_reward_vaults(&self, reward_vaults: Vec<Pubkey>) -> Self {
+        Self {
+            reward_vaults,
+            ..self.clone()
+        }
+    }
+
+    /// Derive a user's stake deposit receipt in this pool
+    pub fn derive_receipt(&self, owner: &Pubkey, nonce: u32) -> (Pubkey, u8) {
+        derive_stake_deposit_receipt_with_program_id(
+            &self.program_id,
+            owner,
+            &self.stake_pool,
+            nonce,
+        )
+    }
+}
+
+/// A difference between a [`PoolConfig`] and the pool on chain
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum PoolConfigMismatch {
+    /// The stake pool account isn't owned by the configured program
+    ProgramOwner { expected: Pubkey, actual: Pubkey },
+    /// The stake pool account doesn't decode as a stake pool
+    InvalidPoolData(String),
+    /// The pool's vault differs
+    Vault { expected: Pubkey, actual: Pubkey },
+    /// The pool's staked mint differs
+    Mint { expected: Pubkey, actual: Pubkey },
+    /// The pool's stake mint differs
+    StakeMint { expected: Pubkey, actual: Pubkey },
+    /// A reward vault differs, is missing (`actual` None) or isn't configured (`expected` None)
+    ///
+    /// `slot` is the reward pool's index in `StakePool.reward_pools`, None for
+    /// configured vaults beyond the pool's initialized reward pools.
+    RewardVault {
+        slot: Option<usize>,
+        expected: Option<Pubkey>,
+        actual: Option<Pubkey>,
+    },
+    /// The staked mint's decimals differ
+    Decimals { expected: u8, actual: u8 },
+}
+
+impl PoolConfigMismatch {
+    /// Whether the client can't work with the config as is
+    ///
+    /// Reward vaults aren't fatal: the client reads the live list from the
+    /// stake pool before sending transactions.
+    pub fn is_fatal(&self) -> bool {
+        !matches!(self, PoolConfigMismatch::RewardVault { .. })
+    }
+}
+
+impl fmt::Display for PoolConfigMismatch {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fn or_none(key: &Option<Pubkey>) -> String {
+            key.map_or_else(|| "none".to_string(), |key| key.to_string())
+        }
+
+        match self {
+            PoolConfigMismatch::ProgramOwner { expected, actual } => {
+                write!(f, "program owner: expected {}, found {}", expected, actual)
+            }
+            PoolConfigMismatch::InvalidPoolData(reason) => {
+                write!(f, "stake pool data: {}", reason)
+            }
+            PoolConfigMismatch::Vault { expected, actual } => {
+                write!(f, "vault: expected {}, found {}", expected, actual)
+            }
+            PoolConfigMismatch::Mint { expected, actual } => {
+                write!(f, "mint: expected {}, found {}", expected, actual)
+            }
+            PoolConfigMismatch::StakeMint { expected, actual } => {
+                write!(f, "stake mint: expected {}, found {}", expected, actual)
+            }
+            PoolConfigMismatch::RewardVault {
+                slot,
+                expected,
+                actual,
+            } => write!(
+                f,
+                "reward vault in slot {}: expected {}, found {}",
+                slot.map_or_else(|| "none".to_string(), |slot| slot.to_string()),
+                or_none(expected),
+                or_none(actual)
+            ),
+            PoolConfigMismatch::Decimals { expected, actual } => {
+                write!(f, "decimals: expected {}, found {}", expected, actual)
+            }
+        }
+    }
+}
+
+/// Every difference between a [`PoolConfig`] and the pool on chain
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct PoolConfigDiff {
+    /// The differences, empty when the config matches
+    pub mismatches: Vec<PoolConfigMismatch>,
+}
+
+impl PoolConfigDiff {
+    /// Whether the config matches the chain
+    pub fn is_empty(&self) -> bool {
+        self.mismatches.is_empty()
+    }
+
+    /// Whether any difference is fatal, see [`PoolConfigMismatch::is_fatal`]
+    pub fn has_fatal(&self) -> bool {
+        self.mismatches.iter().any(PoolConfigMismatch::is_fatal)
+    }
+}
+
+impl fmt::Display for PoolConfigDiff {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.mismatches.is_empty() {
+            return write!(f, "no differences");
+        }
+
+        for (i, mismatch) in self.mismatches.iter().enumerate() {
+            if i > 0 {
+                write!(f, "; ")?;
+            }
+            write!(f, "{}", mismatch)?;
+        }
+        Ok(())
+    }
+}
+
+/// Compare a config against on-chain pool data
+///
+/// Reward vaults are compared per reward pool slot: the config's vaults are
+/// matched, in order, to the pool's initialized slots, the way instructions
+/// pass them to the program.
+///
+/// # Arguments
+/// * `config` - The configured pool
+/// * `owner` - Owner program of the stake pool account
+/// * `stake_pool` - The decoded stake pool
+/// * `decimals` - Decimals of the staked mint, None to skip the check
+pub fn diff_pool_config(
+    config: &PoolConfig,
+    owner: &Pubkey,
+    stake_pool: &StakePool,
+    decimals: Option<u8>,
+) -> PoolConfigDiff {
+    let mut mismatches = Vec::new();
+
+    if *owner != config.program_id {
+        mismatches.push(PoolConfigMismatch::ProgramOwner {
+            expected: config.program_id,
+            actual: *owner,
+        });
+    }
+    if stake_pool.vault != config.vault {
+        mismatches.push(PoolConfigMismatch::Vault {
+            expected: config.vault,
+            actual: stake_pool.vault,
+        });
+    }
+    if stake_pool.mint != config.mint {
+        mismatches.push(PoolConfigMismatch::Mint {
+            expected: config.mint,
+            actual: stake_pool.mint,
+        });
+    }
+    if stake_pool.stake_mint != config.stake_mint {
+        mismatches.push(PoolConfigMismatch::StakeMint {
+            expected: config.stake_mint,
+            actual: stake_pool.stake_mint,
+        });
+    }
+
+    let mut expected_vaults = config.reward_vaults.iter().copied();
+    for (slot, reward_pool) in stake_pool.initialized_reward_pools() {
+        let expected = expected_vaults.next();
+        if expected != Some(reward_pool.reward_vault) {
+            mismatches.push(PoolConfigMismatch::RewardVault {
+                slot: Some(slot),
+                expected,
+                actual: Some(reward_pool.reward_vault),
+            });
+        }
+    }
+    mismatches.extend(expected_vaults.map(|expected| PoolConfigMismatch::RewardVault {
+        slot: None,
+        expected: Some(expected),
+        actual: None,
+    }));
+
+    if let Some(actual) = decimals.filter(|actual| *actual != config.decimals) {
+        mismatches.push(PoolConfigMismatch::Decimals {
+            expected: config.decimals,
+            actual,
+        });
+    }
+
+    PoolConfigDiff { mismatches }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        accounts::{get_user_pool_stake_ata, get_user_pool_token_ata, RewardPool, MAX_REWARD_POOLS},
+        pda::derive_stake_deposit_receipt,
+    };
+    use spl_associated_token_account::get_associated_token_address;
+
+    fn bonk_stake_pool() -> StakePool {
+        let mut reward_pools: [RewardPool; MAX_REWARD_POOLS] = Default::default();
+        reward_pools[0].reward_vault = BONK_REWARD_VAULT_0;
+        StakePool {
+            authority: Pubkey::new_unique(),
+            total_weighted_stake: 0,
+            vault: BONK_VAULT,
+            mint: BONK_MINT,
+            stake_mint: BONK_STAKE_MINT,
+            reward_pools,
+            base_weight: 1_000_000_000,
+            max_weight: 4_000_000_000,
+            min_duration: 2_592_000,
+            max_duration: 31_536_000,
+            nonce: 0,
+            bump_seed: 0,
+        }
+    }
+
+    #[test]
+    fn test_from_stake_pool() {
+        let stake_pool = bonk_stake_pool();
+        let pool = PoolConfig::from_stake_pool(
+            BONK_STAKE_PROGRAM_ID,
+            BONK_STAKE_POOL,
+            &stake_pool,
+            BONK_DECIMALS,
+        );
+        assert_eq!(pool, PoolConfig::bonk_mainnet());
+
+        let user = Pubkey::new_unique();
+        assert_eq!(
+            get_user_pool_token_ata(&user, &pool),
+            get_associated_token_address(&user, &BONK_MINT)
+        );
+        assert_eq!(
+            get_user_pool_stake_ata(&user, &pool),
+            get_associated_token_address(&user, &BONK_STAKE_MINT)
+        );
+        assert_eq!(
+            pool.derive_receipt(&user, 7),
+            derive_stake_deposit_receipt(&user, &BONK_STAKE_POOL, 7)
+        );
+    }
+
+    #[test]
+    fn test_diff_pool_config() {
+        let config = PoolConfig::bonk_mainnet();
+        let mut stake_pool = bonk_stake_pool();
+        assert!(diff_pool_config(&config, &BONK_STAKE_PROGRAM_ID, &stake_pool, Some(BONK_DECIMALS)).is_empty());
+
+        let new_vault = Pubkey::new_unique();
+        stake_pool.reward_pools[1].reward_vault = new_vault;
+        let diff = diff_pool_config(&config, &Pubkey::default(), &stake_pool, Some(6));
+
+        assert_eq!(
+            diff.mismatches,
+            vec![
+                PoolConfigMismatch::ProgramOwner {
+                    expected: BONK_STAKE_PROGRAM_ID,
+                    actual: Pubkey::default(),
+                },
+                PoolConfigMismatch::RewardVault {
+                    slot: Some(1),
+                    expected: None,
+                    actual: Some(new_vault),
+                },
+                PoolConfigMismatch::Decimals { expected: 5, actual: 6 },
+            ]
+        );
+        assert!(diff.has_fatal());
+
+        // Mismatches are reported at the slot holding the vault on chain
+        stake_pool.reward_pools[0] = RewardPool::default();
+        let diff = diff_pool_config(&config, &BONK_STAKE_PROGRAM_ID, &stake_pool, None);
+        assert_eq!(
+            diff.mismatches,
+            vec![PoolConfigMismatch::RewardVault {
+                slot: Some(1),
+                expected: Some(BONK_REWARD_VAULT_0),
+                actual: Some(new_vault),
+            }]
+        );
+        assert!(!diff.has_fatal());
+
+        stake_pool.reward_pools[1] = RewardPool::default();
+        let diff = diff_pool_config(&config, &BONK_STAKE_PROGRAM_ID, &stake_pool, None);
+        assert_eq!(
+            diff.mismatches,
+            vec![PoolConfigMismatch::RewardVault {
+                slot: None,
+                expected: Some(BONK_REWARD_VAULT_0),
+                actual: None,
+            }]
+        );
+    }
+}
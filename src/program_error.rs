@@ -0,0 +1,302 @@
+//! Decoding of spl-token-staking and Anchor framework error codes
+//!
+//! Failed staking transactions come back as `InstructionError::Custom(code)`.
+//! When the failing instruction belongs to the staking program, the code is
+//! mapped to a [`ProgramErrorKind`] and surfaced as
+//! [`BonkStakingError::Program`], together with the program logs.
+
+use solana_client::{
+    client_error::ClientErrorKind,
+    rpc_request::{RpcError, RpcResponseErrorData},
+};
+use solana_sdk::{
+    instruction::{Instruction, InstructionError},
+    pubkey::Pubkey,
+    transaction::TransactionError,
+};
+
+use crate::{error::BonkStakingError, BONK_STAKE_PROGRAM_ID};
+
+/// Known error codes of the staking program and the Anchor framework
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum ProgramErrorKind {
+    // Anchor instruction errors (100-103)
+    /// 8 byte instruction identifier not provided
+    InstructionMissing,
+    /// Fallback functions are not supported
+    InstructionFallbackNotFound,
+    /// The program could not deserialize the given instruction
+    InstructionDidNotDeserialize,
+    /// The program could not serialize the given instruction
+    InstructionDidNotSerialize,
+
+    // Anchor constraint errors (2000-2015)
+    /// A mut constraint was violated
+    ConstraintMut,
+    /// A has_one constraint was violated
+    ConstraintHasOne,
+    /// A signer constraint was violated
+    ConstraintSigner,
+    /// A raw constraint was violated
+    ConstraintRaw,
+    /// An owner constraint was violated
+    ConstraintOwner,
+    /// A rent exemption constraint was violated
+    ConstraintRentExempt,
+    /// A seeds constraint was violated
+    ConstraintSeeds,
+    /// An executable constraint was violated
+    ConstraintExecutable,
+    /// Deprecated state constraint
+    ConstraintState,
+    /// An associated constraint was violated
+    ConstraintAssociated,
+    /// An associated init constraint was violated
+    ConstraintAssociatedInit,
+    /// A close constraint was violated
+    ConstraintClose,
+    /// An address constraint was violated
+    ConstraintAddress,
+    /// Expected zero account discriminant
+    ConstraintZero,
+    /// A token mint constraint was violated
+    ConstraintTokenMint,
+    /// A token owner constraint was violated
+    ConstraintTokenOwner,
+
+    // Anchor account errors (3000-3015)
+    /// The account discriminator was already set on this account
+    AccountDiscriminatorAlreadySet,
+    /// No 8 byte discriminator was found on the account
+    AccountDiscriminatorNotFound,
+    /// The account discriminator did not match what was expected
+    AccountDiscriminatorMismatch,
+    /// Failed to deserialize the account
+    AccountDidNotDeserialize,
+    /// Failed to serialize the account
+    AccountDidNotSerialize,
+    /// Not enough account keys given to the instruction
+    AccountNotEnoughKeys,
+    /// The given account is not mutable
+    AccountNotMutable,
+    /// The given account is owned by a different program than expected
+    AccountOwnedByWrongProgram,
+    /// Program ID was not as expected
+    InvalidProgramId,
+    /// Program account is not executable
+    InvalidProgramExecutable,
+    /// The given account did not sign
+    AccountNotSigner,
+    /// The given account is not owned by the system program
+    AccountNotSystemOwned,
+    /// The program expected this account to be already initialized
+    AccountNotInitialized,
+    /// The given account is not a program data account
+    AccountNotProgramData,
+    /// The given account is not the associated token account
+    AccountNotAssociatedTokenAccount,
+    /// The given public key does not match the required sysvar
+    AccountSysvarMismatch,
+
+    // spl-token-staking errors (6000+)
+    /// The stake pool vault doesn't match the pool
+    InvalidStakePoolVault,
+    /// Reward vaults are missing or not in `StakePool.reward_pools` order
+    InvalidRewardPoolVault,
+    /// Reward pool index is out of range
+    InvalidRewardPoolVaultIndex,
+    /// Arithmetic overflow
+    InvalidOverflow,
+    /// The reward pool slot is already in use
+    RewardPoolIndexOccupied,
+    /// Lockup duration is outside the pool's min/max duration
+    InvalidStakePoolDuration,
+    /// Invalid stake pool weights
+    InvalidStakePoolWeight,
+    /// The position's lockup has not elapsed yet
+    StakeStillLocked,
+
+    /// A code this library doesn't know about
+    Unknown,
+}
+
+impl ProgramErrorKind {
+    /// Map a custom error code to its kind
+    pub fn from_code(code: u32) -> Self {
+        use ProgramErrorKind::*;
+
+        match code {
+            100 => InstructionMissing,
+            101 => InstructionFallbackNotFound,
+            102 => InstructionDidNotDeserialize,
+            103 => InstructionDidNotSerialize,
+            2000 => ConstraintMut,
+            2001 => ConstraintHasOne,
+            2002 => ConstraintSigner,
+            2003 => ConstraintRaw,
+            2004 => ConstraintOwner,
+            2005 => ConstraintRentExempt,
+            2006 => ConstraintSeeds,
+            2007 => ConstraintExecutable,
+            2008 => ConstraintState,
+            2009 => ConstraintAssociated,
+            2010 => ConstraintAssociatedInit,
+            2011 => ConstraintClose,
+            2012 => ConstraintAddress,
+            2013 => ConstraintZero,
+            2014 => ConstraintTokenMint,
+            2015 => ConstraintTokenOwner,
+            3000 => AccountDiscriminatorAlreadySet,
+            3001 => AccountDiscriminatorNotFound,
+            3002 => AccountDiscriminatorMismatch,
+            3003 => AccountDidNotDeserialize,
+            3004 => AccountDidNotSerialize,
+            3005 => AccountNotEnoughKeys,
+            3006 => AccountNotMutable,
+            3007 => AccountOwnedByWrongProgram,
+            3008 => InvalidProgramId,
+            3009 => InvalidProgramExecutable,
+            3010 => AccountNotSigner,
+            3011 => AccountNotSystemOwned,
+            3012 => AccountNotInitialized,
+            3013 => AccountNotProgramData,
+            3014 => AccountNotAssociatedTokenAccount,
+            3015 => AccountSysvarMismatch,
+            6000 => InvalidStakePoolVault,
+            6001 => InvalidRewardPoolVault,
+            6002 => InvalidRewardPoolVaultIndex,
+            6003 => InvalidOverflow,
+            6004 => RewardPoolIndexOccupied,
+            6005 => InvalidStakePoolDuration,
+            6006 => InvalidStakePoolWeight,
+            6007 => StakeStillLocked,
+            _ => Unknown,
+        }
+    }
+
+    /// Whether the error comes from the Anchor framework rather than the program
+    pub fn is_anchor_error(&self) -> bool {
+        !matches!(
+            self,
+            ProgramErrorKind::InvalidStakePoolVault
+                | ProgramErrorKind::InvalidRewardPoolVault
+                | ProgramErrorKind::InvalidRewardPoolVaultIndex
+                | ProgramErrorKind::InvalidOverflow
+                | ProgramErrorKind::RewardPoolIndexOccupied
+                | ProgramErrorKind::InvalidStakePoolDuration
+                | ProgramErrorKind::InvalidStakePoolWeight
+                | ProgramErrorKind::StakeStillLocked
+                | ProgramErrorKind::Unknown
+        )
+    }
+}
+
+/// Decode a failed transaction's error into a staking program error
+///
+/// # Arguments
+/// * `err` - The transaction error
+/// * `instructions` - The transaction's instructions, used to check which
+///   program failed
+/// * `logs` - Program logs, if any
+///
+/// # Returns
+/// `BonkStakingError::Program` if a staking program instruction failed with a
+/// custom error, None otherwise
+pub fn decode_transaction_error(
+    err: &TransactionError,
+    instructions: &[Instruction],
+    logs: Vec<String>,
+) -> Option<BonkStakingError> {
+    decode_transaction_error_for_program(err, &BONK_STAKE_PROGRAM_ID, instructions, logs)
+}
+
+/// Decode a failed transaction's error for a deployment of the staking program
+///
+/// Same as [`decode_transaction_error`], for pools owned by `program_id`.
+pub fn decode_transaction_error_for_program(
+    err: &TransactionError,
+    program_id: &Pubkey,
+    instructions: &[Instruction],
+    logs: Vec<String>,
+) -> Option<BonkStakingError> {
+    let TransactionError::InstructionError(index, InstructionError::Custom(code)) = err else {
+        return None;
+    };
+
+    let instruction = instructions.get(*index as usize)?;
+    if instruction.program_id != *program_id {
+        return None;
+    }
+
+    Some(BonkStakingError::Program {
+        code: *code,
+        kind: ProgramErrorKind::from_code(*code),
+        logs,
+    })
+}
+
+/// Turn a failed send into the most specific error available
+///
+/// Staking program failures, including preflight simulation failures, become
+/// `BonkStakingError::Program`; other RPC failures are `TransactionFailed`.
+/// Errors that didn't come from the RPC client are returned as is.
+pub(crate) fn send_error(err: BonkStakingError, program_id: &Pubkey, instructions: &[Instruction]) -> BonkStakingError {
+    let BonkStakingError::ClientError(err) = err else {
+        return err;
+    };
+    let decoded = match err.kind() {
+        ClientErrorKind::TransactionError(tx_err) => {
+            decode_transaction_error_for_program(tx_err, program_id, instructions, Vec::new())
+        }
+        ClientErrorKind::RpcError(RpcError::RpcResponseError {
+            data: RpcResponseErrorData::SendTransactionPreflightFailure(result),
+            ..
+        }) => result.err.as_ref().and_then(|tx_err| {
+            decode_transaction_error_for_program(tx_err, program_id, instructions, result.logs.clone().unwrap_or_default())
+        }),
+        _ => None,
+    };
+
+    decoded.unwrap_or_else(|| BonkStakingError::TransactionFailed(err.to_string()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::instructions::{build_claim_all_instruction, build_compute_budget_price_instruction};
+
+    #[test]
+    fn test_error_codes() {
+        assert_eq!(ProgramErrorKind::from_code(6007), ProgramErrorKind::StakeStillLocked);
+        assert_eq!(ProgramErrorKind::from_code(3002), ProgramErrorKind::AccountDiscriminatorMismatch);
+        assert_eq!(ProgramErrorKind::from_code(2006), ProgramErrorKind::ConstraintSeeds);
+        assert_eq!(ProgramErrorKind::from_code(42), ProgramErrorKind::Unknown);
+        assert!(ProgramErrorKind::ConstraintSeeds.is_anchor_error());
+        assert!(!ProgramErrorKind::StakeStillLocked.is_anchor_error());
+    }
+
+    #[test]
+    fn test_only_staking_program_failures_are_decoded() {
+        let owner = Pubkey::new_unique();
+        let instructions = [
+            build_compute_budget_price_instruction(5045),
+            build_claim_all_instruction(&owner, 0, &[]),
+        ];
+
+        let staking_err = TransactionError::InstructionError(1, InstructionError::Custom(6007));
+        match decode_transaction_error(&staking_err, &instructions, vec!["log".to_string()]) {
+            Some(BonkStakingError::Program { code, kind, logs }) => {
+                assert_eq!(code, 6007);
+                assert_eq!(kind, ProgramErrorKind::StakeStillLocked);
+                assert_eq!(logs, vec!["log".to_string()]);
+            }
+            other => panic!("unexpected {:?}", other),
+        }
+
+        let other_err = TransactionError::InstructionError(0, InstructionError::Custom(6007));
+        assert!(decode_transaction_error(&other_err, &instructions, Vec::new()).is_none());
+
+        let other_program = Pubkey::new_unique();
+        assert!(decode_transaction_error_for_program(&staking_err, &other_program, &instructions, Vec::new()).is_none());
+    }
+}
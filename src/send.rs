@@ -0,0 +1,204 @@
+//! Send pipeline with rebroadcasting and blockhash expiry handling
+//!
+//! A signed transaction is rebroadcast at a fixed interval until it reaches
+//! the target commitment, fails, or its blockhash expires. It is only re-signed
+//! with a fresh blockhash once the original is provably expired: the block
+//! height has passed its `lastValidBlockHeight` and the signature was never
+//! seen. Re-signing earlier could land the same deposit twice.
+
+use std::{thread, time::Duration};
+
+use solana_client::rpc_config::RpcSendTransactionConfig;
+use solana_sdk::{
+    commitment_config::CommitmentConfig, instruction::Instruction, pubkey::Pubkey,
+    signature::Signature, signer::Signer, transaction::VersionedTransaction,
+};
+
+use crate::{
+    error::{BonkStakingError, Result},
+    program_error::{decode_transaction_error_for_program, send_error},
+    transaction::{build_unsigned_transaction, TransactionVersion},
+    transport::RpcTransport,
+};
+
+/// Send pipeline settings
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SendConfig {
+    /// Commitment a transaction must reach to count as landed
+    pub commitment: CommitmentConfig,
+    /// Time between status checks and rebroadcasts
+    pub rebroadcast_interval: Duration,
+    /// How many times an expired transaction is re-signed with a fresh blockhash
+    pub max_resigns: u32,
+}
+
+impl Default for SendConfig {
+    fn default() -> Self {
+        Self {
+            commitment: CommitmentConfig::confirmed(),
+            rebroadcast_interval: Duration::from_secs(2),
+            max_resigns: 1,
+        }
+    }
+}
+
+/// How a sent transaction ended up
+#[derive(Debug)]
+pub enum SendOutcome {
+    /// The transaction reached the target commitment
+    Landed { signature: Signature, slot: u64 },
+    /// The last blockhash expired without the transaction landing
+    Expired { signature: Signature },
+    /// The transaction landed but failed
+    Failed { signature: Signature, error: BonkStakingError },
+}
+
+/// Sign and send a transaction, rebroadcasting until it lands or expires
+///
+/// The first broadcast runs preflight checks, so a transaction the program
+/// would reject returns an error without being sent.
+///
+/// # Arguments
+/// * `rpc` - RPC transport
+/// * `program_id` - Staking program whose errors are decoded
+/// * `instructions` - Instructions to send
+/// * `payer` - Fee payer, must be one of the signers
+/// * `signers` - Every required signer, each once
+/// * `version` - Message format
+/// * `config` - Send pipeline settings
+pub fn send_with_rebroadcast<T: RpcTransport + ?Sized>(
+    rpc: &T,
+    program_id: &Pubkey,
+    instructions: &[Instruction],
+    payer: &Pubkey,
+    signers: &[&dyn Signer],
+    version: TransactionVersion,
+    config: &SendConfig,
+) -> Result<SendOutcome> {
+    let preflight_config = RpcSendTransactionConfig {
+        preflight_commitment: Some(config.commitment.commitment),
+        max_retries: Some(0),
+        ..RpcSendTransactionConfig::default()
+    };
+    let rebroadcast_config = RpcSendTransactionConfig {
+        skip_preflight: true,
+        ..preflight_config
+    };
+
+    let mut resigns = 0;
+    loop {
+        let (blockhash, last_valid_block_height) = rpc.get_latest_blockhash_with_commitment(config.commitment)?;
+        let unsigned = build_unsigned_transaction(instructions, payer, blockhash, version)?;
+        let transaction = VersionedTransaction::try_new(unsigned.message, signers)
+            .map_err(|e| BonkStakingError::TransactionFailed(e.to_string()))?;
+        let signature = transaction.signatures[0];
+
+        rpc.send_transaction(&transaction, preflight_config)
+            .map_err(|e| send_error(e, program_id, instructions))?;
+
+        loop {
+            thread::sleep(config.rebroadcast_interval);
+
+            // Read the block height before the status, so a missing status
+            // proves the transaction can no longer land
+            let block_height = rpc.get_block_height(config.commitment)?;
+            let status = rpc.get_signature_statuses(&[signature])?.into_iter().next().flatten();
+
+            if let Some(status) = status {
+                if let Some(err) = status.err {
+                    let error = decode_transaction_error_for_program(&err, program_id, instructions, Vec::new())
+                        .unwrap_or_else(|| BonkStakingError::TransactionFailed(err.to_string()));
+                    return Ok(SendOutcome::Failed { signature, error });
+                }
+                if status.satisfies_commitment(config.commitment) {
+                    return Ok(SendOutcome::Landed {
+                        signature,
+                        slot: status.slot,
+                    });
+                }
+                continue;
+            }
+
+            if block_height > last_valid_block_height {
+                break;
+            }
+
+            if let Err(err) = rpc.send_transaction(&transaction, rebroadcast_config) {
+                log::debug!("Rebroadcast of {} failed: {}", signature, err);
+            }
+        }
+
+        if resigns >= config.max_resigns {
+            return Ok(SendOutcome::Expired { signature });
+        }
+        resigns += 1;
+        log::debug!("{} expired, re-signing with a fresh blockhash", signature);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        instructions::build_claim_all_instruction, transport::fake::FakeTransport, BONK_STAKE_PROGRAM_ID,
+    };
+    use solana_sdk::signature::Keypair;
+    use solana_transaction_status_client_types::{TransactionConfirmationStatus, TransactionStatus};
+
+    /// Transport whose block height passes the blockhash's last valid height on every second read
+    fn scripted_transport(status: Option<TransactionStatus>) -> FakeTransport {
+        FakeTransport::scripted(vec![100, 200], 150, status)
+    }
+
+    fn send(rpc: &FakeTransport, max_resigns: u32) -> SendOutcome {
+        let owner = Keypair::new();
+        let instructions = [build_claim_all_instruction(&owner.pubkey(), 0, &[])];
+        let config = SendConfig {
+            rebroadcast_interval: Duration::ZERO,
+            max_resigns,
+            ..SendConfig::default()
+        };
+
+        send_with_rebroadcast(
+            rpc,
+            &BONK_STAKE_PROGRAM_ID,
+            &instructions,
+            &owner.pubkey(),
+            &[&owner],
+            TransactionVersion::Legacy,
+            &config,
+        )
+        .unwrap()
+    }
+
+    #[test]
+    fn test_landed_transaction() {
+        let rpc = scripted_transport(Some(TransactionStatus {
+            slot: 42,
+            confirmations: Some(10),
+            status: Ok(()),
+            err: None,
+            confirmation_status: Some(TransactionConfirmationStatus::Confirmed),
+        }));
+
+        assert!(matches!(send(&rpc, 1), SendOutcome::Landed { slot: 42, .. }));
+        assert_eq!(rpc.sent.borrow().len(), 1);
+    }
+
+    #[test]
+    fn test_resigns_only_after_expiry() {
+        let rpc = scripted_transport(None);
+
+        let SendOutcome::Expired { signature } = send(&rpc, 1) else {
+            panic!("expected the transaction to expire");
+        };
+
+        // Each blockhash is sent, rebroadcast once while still valid, then
+        // re-signed only after the block height passed its last valid height
+        let sent = rpc.sent.borrow();
+        assert_eq!(sent.len(), 4);
+        assert_eq!(sent[0], sent[1]);
+        assert_ne!(sent[1], sent[2]);
+        assert_eq!(sent[3], signature);
+    }
+}
@@ -0,0 +1,220 @@
+//! Pre-flight simulation of staking transactions
+//!
+//! Simulates a transaction with `simulateTransaction`, asking the RPC for the
+//! post-simulation state of the owner's token accounts and the stake deposit
+//! receipt, and turns the before/after difference into a [`TransactionPreview`].
+
+use solana_account_decoder_client_types::UiAccountEncoding;
+use solana_client::{
+    rpc_config::{RpcSimulateTransactionAccountsConfig, RpcSimulateTransactionConfig},
+    rpc_response::RpcSimulateTransactionResult,
+};
+use solana_sdk::{account::Account, instruction::Instruction, pubkey::Pubkey};
+
+use crate::{
+    client::read_token_balance,
+    error::{BonkStakingError, Result},
+    program_error::decode_transaction_error_for_program,
+};
+
+/// What a staking transaction would do, according to a simulation
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TransactionPreview {
+    /// Change of the owner's balance of the pool's token (negative when debited)
+    pub token_change: i128,
+    /// Change of the owner's stake token balance (positive when stake tokens are minted)
+    pub stake_token_change: i128,
+    /// Whether the stake deposit receipt account is created
+    pub receipt_created: bool,
+    /// Whether the stake deposit receipt account is closed
+    pub receipt_closed: bool,
+    /// Lamports deposited as rent into newly created accounts
+    pub rent_lamports: u64,
+    /// Compute units consumed by the simulation
+    pub units_consumed: Option<u64>,
+    /// Program logs
+    pub logs: Vec<String>,
+}
+
+/// Accounts whose state is compared before and after a simulation
+pub(crate) struct WatchedAccounts {
+    /// Owner's token account for the pool's mint
+    pub token_account: Pubkey,
+    /// Owner's stake token account
+    pub stake_account: Pubkey,
+    /// Stake deposit receipt
+    pub receipt: Pubkey,
+    /// Other accounts the transaction may create, e.g. reward token accounts
+    pub other_accounts: Vec<Pubkey>,
+}
+
+impl WatchedAccounts {
+    /// Addresses in the order used for the before and after states
+    pub(crate) fn addresses(&self) -> Vec<Pubkey> {
+        let mut addresses = vec![self.token_account, self.stake_account, self.receipt];
+        for address in &self.other_accounts {
+            if !addresses.contains(address) {
+                addresses.push(*address);
+            }
+        }
+        addresses
+    }
+}
+
+/// Build the `simulateTransaction` config returning the watched accounts
+///
+/// Signatures aren't verified and the blockhash is replaced, so unsigned
+/// transactions can be simulated.
+pub(crate) fn simulation_config(addresses: &[Pubkey]) -> RpcSimulateTransactionConfig {
+    RpcSimulateTransactionConfig {
+        sig_verify: false,
+        replace_recent_blockhash: true,
+        accounts: Some(RpcSimulateTransactionAccountsConfig {
+            encoding: Some(UiAccountEncoding::Base64),
+            addresses: addresses.iter().map(|address| address.to_string()).collect(),
+        }),
+        ..RpcSimulateTransactionConfig::default()
+    }
+}
+
+/// Turn a simulation result into a preview
+///
+/// # Arguments
+/// * `program_id` - Staking program whose errors are decoded
+/// * `instructions` - The simulated instructions
+/// * `watched` - The accounts requested in the simulation config
+/// * `before` - Their current state, in `watched.addresses()` order
+/// * `result` - The simulation result
+pub(crate) fn preview_from_simulation(
+    program_id: &Pubkey,
+    instructions: &[Instruction],
+    watched: &WatchedAccounts,
+    before: &[Option<Account>],
+    result: RpcSimulateTransactionResult,
+) -> Result<TransactionPreview> {
+    let logs = result.logs.unwrap_or_default();
+    if let Some(err) = result.err {
+        return Err(
+            decode_transaction_error_for_program(&err, program_id, instructions, logs.clone()).unwrap_or_else(|| {
+                BonkStakingError::SimulationFailed {
+                    message: err.to_string(),
+                    logs,
+                }
+            }),
+        );
+    }
+
+    let after = result
+        .accounts
+        .unwrap_or_default()
+        .into_iter()
+        .map(|account| account.and_then(|account| account.decode::<Account>()))
+        // Accounts closed by the transaction come back empty
+        .map(|account| account.filter(|account| account.lamports > 0))
+        .collect::<Vec<_>>();
+
+    let addresses = watched.addresses();
+    if before.len() != addresses.len() || after.len() != addresses.len() {
+        return Err(BonkStakingError::InvalidAccountData(
+            "Simulation returned an unexpected number of accounts".to_string(),
+        ));
+    }
+
+    let balance_change = |index: usize| -> Result<i128> {
+        let before = read_token_balance(before[index].as_ref())?;
+        let after = read_token_balance(after[index].as_ref())?;
+        Ok(after as i128 - before as i128)
+    };
+
+    let rent_lamports = before
+        .iter()
+        .zip(&after)
+        .filter(|(before, _)| before.is_none())
+        .filter_map(|(_, after)| after.as_ref())
+        .map(|account| account.lamports)
+        .sum();
+
+    Ok(TransactionPreview {
+        token_change: balance_change(0)?,
+        stake_token_change: balance_change(1)?,
+        receipt_created: before[2].is_none() && after[2].is_some(),
+        receipt_closed: before[2].is_some() && after[2].is_none(),
+        rent_lamports,
+        units_consumed: result.units_consumed,
+        logs,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use base64::{engine::general_purpose::STANDARD, Engine as _};
+    use serde_json::{json, Value};
+    use solana_sdk::program_pack::Pack;
+
+    fn token_account(amount: u64) -> Account {
+        let mut data = vec![0; spl_token::state::Account::LEN];
+        spl_token::state::Account {
+            amount,
+            state: spl_token::state::AccountState::Initialized,
+            ..Default::default()
+        }
+        .pack_into_slice(&mut data);
+        Account {
+            lamports: 2_039_280,
+            data,
+            owner: spl_token::id(),
+            executable: false,
+            rent_epoch: 0,
+        }
+    }
+
+    fn ui_account(account: &Account) -> Value {
+        json!({
+            "lamports": account.lamports,
+            "data": [STANDARD.encode(&account.data), "base64"],
+            "owner": account.owner.to_string(),
+            "executable": false,
+            "rentEpoch": 0,
+        })
+    }
+
+    #[test]
+    fn test_stake_preview() {
+        let watched = WatchedAccounts {
+            token_account: Pubkey::new_unique(),
+            stake_account: Pubkey::new_unique(),
+            receipt: Pubkey::new_unique(),
+            other_accounts: vec![],
+        };
+        let receipt = Account {
+            lamports: 3_006_720,
+            data: vec![0; 304],
+            owner: crate::BONK_STAKE_PROGRAM_ID,
+            executable: false,
+            rent_epoch: 0,
+        };
+        let before = [Some(token_account(50_000_000)), None, None];
+        let result: RpcSimulateTransactionResult = serde_json::from_value(json!({
+            "err": null,
+            "logs": ["Program STAKEkKzbdeKkqzKpLkNQD3SUuLgshDKCD7U8duxAbB success"],
+            "accounts": [
+                ui_account(&token_account(40_000_000)),
+                ui_account(&token_account(20_000_000)),
+                ui_account(&receipt),
+            ],
+            "unitsConsumed": 61_234,
+        }))
+        .unwrap();
+
+        let preview = preview_from_simulation(&crate::BONK_STAKE_PROGRAM_ID, &[], &watched, &before, result).unwrap();
+
+        assert_eq!(preview.token_change, -10_000_000);
+        assert_eq!(preview.stake_token_change, 20_000_000);
+        assert!(preview.receipt_created);
+        assert!(!preview.receipt_closed);
+        assert_eq!(preview.rent_lamports, 2_039_280 + 3_006_720);
+        assert_eq!(preview.units_consumed, Some(61_234));
+        assert_eq!(preview.logs.len(), 1);
+    }
+}
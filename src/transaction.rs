@@ -0,0 +1,104 @@
+//! Unsigned transactions for wallet-adapter and external-signer flows
+//!
+//! The client's `build_*_transaction` methods return transactions with the
+//! recent blockhash filled in and every signature left empty, ready to be
+//! signed by a browser wallet, hardware wallet or remote signer.
+
+use base64::{engine::general_purpose::STANDARD, Engine as _};
+use solana_sdk::{
+    hash::Hash,
+    instruction::Instruction,
+    message::{v0, Message, VersionedMessage},
+    pubkey::Pubkey,
+    signature::Signature,
+    transaction::VersionedTransaction,
+};
+
+use crate::error::{BonkStakingError, Result};
+
+/// Message format of built transactions
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum TransactionVersion {
+    /// Legacy message, supported by every wallet
+    #[default]
+    Legacy,
+    /// Version 0 message (without address lookup tables)
+    V0,
+}
+
+/// Build an unsigned transaction
+///
+/// # Arguments
+/// * `instructions` - Instructions to include
+/// * `fee_payer` - Account paying the transaction fee (first signer)
+/// * `recent_blockhash` - Recent blockhash the transaction expires with
+/// * `version` - Message format
+///
+/// # Returns
+/// A transaction with one empty signature slot per required signer
+pub fn build_unsigned_transaction(
+    instructions: &[Instruction],
+    fee_payer: &Pubkey,
+    recent_blockhash: Hash,
+    version: TransactionVersion,
+) -> Result<VersionedTransaction> {
+    let message = match version {
+        TransactionVersion::Legacy => VersionedMessage::Legacy(Message::new_with_blockhash(
+            instructions,
+            Some(fee_payer),
+            &recent_blockhash,
+        )),
+        TransactionVersion::V0 => VersionedMessage::V0(
+            v0::Message::try_compile(fee_payer, instructions, &[], recent_blockhash)
+                .map_err(|e| BonkStakingError::TransactionFailed(e.to_string()))?,
+        ),
+    };
+
+    let num_signatures = message.header().num_required_signatures as usize;
+    Ok(VersionedTransaction {
+        signatures: vec![Signature::default(); num_signatures],
+        message,
+    })
+}
+
+/// Serialize a transaction to base64, the format wallet adapters expect
+pub fn encode_transaction_base64(transaction: &VersionedTransaction) -> Result<String> {
+    let bytes = bincode::serialize(transaction)
+        .map_err(|e| BonkStakingError::SerializationError(std::io::Error::other(e)))?;
+    Ok(STANDARD.encode(bytes))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::instructions::build_claim_all_instruction;
+
+    #[test]
+    fn test_unsigned_transaction_has_empty_signer_slots() {
+        let owner = Pubkey::new_unique();
+        let fee_payer = Pubkey::new_unique();
+        let blockhash = Hash::new_unique();
+        let instructions = [build_claim_all_instruction(&owner, 0, &[])];
+
+        for version in [TransactionVersion::Legacy, TransactionVersion::V0] {
+            let transaction = build_unsigned_transaction(&instructions, &fee_payer, blockhash, version).unwrap();
+
+            assert_eq!(transaction.signatures, vec![Signature::default(); 2]);
+            assert_eq!(transaction.message.static_account_keys()[0], fee_payer);
+            assert_eq!(transaction.message.static_account_keys()[1], owner);
+            assert_eq!(*transaction.message.recent_blockhash(), blockhash);
+        }
+    }
+
+    #[test]
+    fn test_encode_transaction_base64_roundtrip() {
+        let owner = Pubkey::new_unique();
+        let instructions = [build_claim_all_instruction(&owner, 0, &[])];
+        let transaction =
+            build_unsigned_transaction(&instructions, &owner, Hash::new_unique(), TransactionVersion::V0).unwrap();
+
+        let encoded = encode_transaction_base64(&transaction).unwrap();
+        let decoded: VersionedTransaction = bincode::deserialize(&STANDARD.decode(encoded).unwrap()).unwrap();
+        assert_eq!(decoded, transaction);
+    }
+}
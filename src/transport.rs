@@ -0,0 +1,314 @@
+//! Pluggable RPC transport for the blocking client
+//!
+//! [`crate::client::BonkStakingClient`] only talks to Solana through the
+//! [`RpcTransport`] trait, so it can run on top of a recording/replaying fake,
+//! a caching layer or a failover pool instead of a plain `RpcClient`.
+
+use solana_client::{
+    client_error::ClientError,
+    rpc_client::RpcClient,
+    rpc_config::{RpcProgramAccountsConfig, RpcSendTransactionConfig, RpcSimulateTransactionConfig},
+    rpc_request::RpcError,
+    rpc_response::{RpcPrioritizationFee, RpcSimulateTransactionResult},
+};
+use solana_sdk::{
+    account::Account, commitment_config::CommitmentConfig, hash::Hash, pubkey::Pubkey,
+    signature::Signature, transaction::VersionedTransaction,
+};
+use solana_transaction_status_client_types::TransactionStatus;
+
+use crate::error::Result;
+
+/// The RPC calls used by the BONK staking client
+///
+/// Methods return the crate's [`Result`], with RPC failures as
+/// [`BonkStakingError::ClientError`](crate::error::BonkStakingError::ClientError).
+/// Wrappers can return any other variant for failures of their own.
+pub trait RpcTransport {
+    /// Fetch an account, None if it doesn't exist
+    fn get_account(&self, address: &Pubkey) -> Result<Option<Account>>;
+
+    /// Fetch several accounts in one request, None for the ones that don't exist
+    fn get_multiple_accounts(&self, addresses: &[Pubkey]) -> Result<Vec<Option<Account>>>;
+
+    /// Fetch all accounts owned by a program that match the config's filters
+    fn get_program_accounts(
+        &self,
+        program_id: &Pubkey,
+        config: RpcProgramAccountsConfig,
+    ) -> Result<Vec<(Pubkey, Account)>>;
+
+    /// Get a token account's balance (in the mint's smallest unit)
+    fn get_token_account_balance(&self, address: &Pubkey) -> Result<u64>;
+
+    /// Get the latest blockhash
+    fn get_latest_blockhash(&self) -> Result<Hash>;
+
+    /// Get the latest blockhash and the last block height it is valid for
+    fn get_latest_blockhash_with_commitment(&self, commitment: CommitmentConfig) -> Result<(Hash, u64)>;
+
+    /// Get the current block height
+    fn get_block_height(&self, commitment: CommitmentConfig) -> Result<u64>;
+
+    /// Get the statuses of recently processed transactions
+    fn get_signature_statuses(&self, signatures: &[Signature]) -> Result<Vec<Option<TransactionStatus>>>;
+
+    /// Get the prioritization fees paid recently by transactions writing to all of the addresses
+    fn get_recent_prioritization_fees(&self, addresses: &[Pubkey]) -> Result<Vec<RpcPrioritizationFee>>;
+
+    /// Simulate a transaction
+    fn simulate_transaction(
+        &self,
+        transaction: &VersionedTransaction,
+        config: RpcSimulateTransactionConfig,
+    ) -> Result<RpcSimulateTransactionResult>;
+
+    /// Send a transaction without waiting for confirmation
+    fn send_transaction(
+        &self,
+        transaction: &VersionedTransaction,
+        config: RpcSendTransactionConfig,
+    ) -> Result<Signature>;
+}
+
+impl RpcTransport for RpcClient {
+    fn get_account(&self, address: &Pubkey) -> Result<Option<Account>> {
+        Ok(self.get_account_with_commitment(address, self.commitment())?.value)
+    }
+
+    fn get_multiple_accounts(&self, addresses: &[Pubkey]) -> Result<Vec<Option<Account>>> {
+        Ok(RpcClient::get_multiple_accounts(self, addresses)?)
+    }
+
+    fn get_program_accounts(
+        &self,
+        program_id: &Pubkey,
+        config: RpcProgramAccountsConfig,
+    ) -> Result<Vec<(Pubkey, Account)>> {
+        Ok(self.get_program_accounts_with_config(program_id, config)?)
+    }
+
+    fn get_token_account_balance(&self, address: &Pubkey) -> Result<u64> {
+        let balance = RpcClient::get_token_account_balance(self, address)?;
+        balance.amount.parse().map_err(|_| {
+            ClientError::from(RpcError::ParseError(format!("Invalid token amount: {}", balance.amount))).into()
+        })
+    }
+
+    fn get_latest_blockhash(&self) -> Result<Hash> {
+        Ok(RpcClient::get_latest_blockhash(self)?)
+    }
+
+    fn get_latest_blockhash_with_commitment(&self, commitment: CommitmentConfig) -> Result<(Hash, u64)> {
+        Ok(RpcClient::get_latest_blockhash_with_commitment(self, commitment)?)
+    }
+
+    fn get_block_height(&self, commitment: CommitmentConfig) -> Result<u64> {
+        Ok(self.get_block_height_with_commitment(commitment)?)
+    }
+
+    fn get_signature_statuses(&self, signatures: &[Signature]) -> Result<Vec<Option<TransactionStatus>>> {
+        Ok(RpcClient::get_signature_statuses(self, signatures)?.value)
+    }
+
+    fn get_recent_prioritization_fees(&self, addresses: &[Pubkey]) -> Result<Vec<RpcPrioritizationFee>> {
+        Ok(RpcClient::get_recent_prioritization_fees(self, addresses)?)
+    }
+
+    fn simulate_transaction(
+        &self,
+        transaction: &VersionedTransaction,
+        config: RpcSimulateTransactionConfig,
+    ) -> Result<RpcSimulateTransactionResult> {
+        Ok(self.simulate_transaction_with_config(transaction, config)?.value)
+    }
+
+    fn send_transaction(
+        &self,
+        transaction: &VersionedTransaction,
+        config: RpcSendTransactionConfig,
+    ) -> Result<Signature> {
+        Ok(self.send_transaction_with_config(transaction, config)?)
+    }
+}
+
+/// Configurable in-memory transport for unit tests
+///
+/// Anything not configured answers with an RPC error rather than panicking,
+/// so tests exercise the client's error paths like a real endpoint would.
+#[cfg(test)]
+pub(crate) mod fake {
+    use std::{
+        cell::{Cell, RefCell},
+        collections::HashMap,
+    };
+
+    use solana_client::{
+        client_error::ClientError,
+        rpc_config::{RpcProgramAccountsConfig, RpcSendTransactionConfig, RpcSimulateTransactionConfig},
+        rpc_filter::RpcFilterType,
+        rpc_request::{RpcError, RpcResponseErrorData},
+        rpc_response::{RpcPrioritizationFee, RpcSimulateTransactionResult},
+    };
+    use solana_sdk::{
+        account::Account, commitment_config::CommitmentConfig, hash::Hash, program_pack::Pack,
+        pubkey::Pubkey, signature::Signature, transaction::VersionedTransaction,
+    };
+    use solana_transaction_status_client_types::TransactionStatus;
+
+    use super::RpcTransport;
+    use crate::error::Result;
+
+    /// JSON-RPC error an endpoint answers for a method it doesn't serve
+    pub(crate) fn method_not_found(method: &str) -> ClientError {
+        RpcError::RpcResponseError {
+            code: -32601,
+            message: format!("Method not found: {}", method),
+            data: RpcResponseErrorData::Empty,
+        }
+        .into()
+    }
+
+    /// Transport serving fixed accounts and scripted chain state
+    #[derive(Default)]
+    pub(crate) struct FakeTransport {
+        /// Accounts served by address
+        pub accounts: HashMap<Pubkey, Account>,
+        /// Accounts searched by getProgramAccounts filters, None to answer "method not found"
+        pub program_accounts: Option<Vec<(Pubkey, Account)>>,
+        /// Block heights returned by successive reads, cycling
+        pub block_heights: Vec<u64>,
+        /// Last valid block height of every served blockhash
+        pub last_valid_block_height: u64,
+        /// Status returned for every signature
+        pub signature_status: Option<TransactionStatus>,
+        /// Simulation result, None to fail simulations
+        pub simulation: Option<RpcSimulateTransactionResult>,
+        /// Recent prioritization fees
+        pub prioritization_fees: Vec<RpcPrioritizationFee>,
+        /// Number of addresses in each getMultipleAccounts call, in order
+        pub multiple_accounts_reads: RefCell<Vec<usize>>,
+        /// Signatures of the sent transactions, in order
+        pub sent: RefCell<Vec<Signature>>,
+        block_height_reads: Cell<usize>,
+    }
+
+    impl FakeTransport {
+        /// Transport whose chain advances through `block_heights` and reports
+        /// `status` for every signature
+        pub fn scripted(
+            block_heights: Vec<u64>,
+            last_valid_block_height: u64,
+            status: Option<TransactionStatus>,
+        ) -> Self {
+            Self {
+                block_heights,
+                last_valid_block_height,
+                signature_status: status,
+                ..Self::default()
+            }
+        }
+
+        /// Serve an account at an address
+        pub fn with_account(mut self, address: Pubkey, account: Account) -> Self {
+            self.accounts.insert(address, account);
+            self
+        }
+
+        /// Answer getProgramAccounts from these accounts
+        pub fn with_program_accounts(mut self, accounts: Vec<(Pubkey, Account)>) -> Self {
+            self.program_accounts = Some(accounts);
+            self
+        }
+    }
+
+    impl RpcTransport for FakeTransport {
+        fn get_account(&self, address: &Pubkey) -> Result<Option<Account>> {
+            Ok(self.accounts.get(address).cloned())
+        }
+
+        fn get_multiple_accounts(&self, addresses: &[Pubkey]) -> Result<Vec<Option<Account>>> {
+            self.multiple_accounts_reads.borrow_mut().push(addresses.len());
+            Ok(addresses.iter().map(|address| self.accounts.get(address).cloned()).collect())
+        }
+
+        fn get_program_accounts(
+            &self,
+            _program_id: &Pubkey,
+            config: RpcProgramAccountsConfig,
+        ) -> Result<Vec<(Pubkey, Account)>> {
+            let accounts = self
+                .program_accounts
+                .as_ref()
+                .ok_or_else(|| method_not_found("getProgramAccounts"))?;
+            let filters = config.filters.unwrap_or_default();
+
+            Ok(accounts
+                .iter()
+                .filter(|(_, account)| {
+                    filters.iter().all(|filter| match filter {
+                        RpcFilterType::Memcmp(memcmp) => memcmp.bytes_match(&account.data),
+                        RpcFilterType::DataSize(size) => account.data.len() as u64 == *size,
+                        RpcFilterType::TokenAccountState => true,
+                    })
+                })
+                .cloned()
+                .collect())
+        }
+
+        fn get_token_account_balance(&self, address: &Pubkey) -> Result<u64> {
+            self.accounts
+                .get(address)
+                .and_then(|account| spl_token::state::Account::unpack(&account.data).ok())
+                .map(|token_account| token_account.amount)
+                .ok_or_else(|| {
+                    ClientError::from(RpcError::ForUser(format!("Invalid param: could not find account {}", address)))
+                        .into()
+                })
+        }
+
+        fn get_latest_blockhash(&self) -> Result<Hash> {
+            Ok(Hash::new_unique())
+        }
+
+        fn get_latest_blockhash_with_commitment(&self, _commitment: CommitmentConfig) -> Result<(Hash, u64)> {
+            Ok((Hash::new_unique(), self.last_valid_block_height))
+        }
+
+        fn get_block_height(&self, _commitment: CommitmentConfig) -> Result<u64> {
+            if self.block_heights.is_empty() {
+                return Err(method_not_found("getBlockHeight").into());
+            }
+            let reads = self.block_height_reads.get();
+            self.block_height_reads.set(reads + 1);
+            Ok(self.block_heights[reads % self.block_heights.len()])
+        }
+
+        fn get_signature_statuses(&self, signatures: &[Signature]) -> Result<Vec<Option<TransactionStatus>>> {
+            Ok(signatures.iter().map(|_| self.signature_status.clone()).collect())
+        }
+
+        fn get_recent_prioritization_fees(&self, _addresses: &[Pubkey]) -> Result<Vec<RpcPrioritizationFee>> {
+            Ok(self.prioritization_fees.clone())
+        }
+
+        fn simulate_transaction(
+            &self,
+            _transaction: &VersionedTransaction,
+            _config: RpcSimulateTransactionConfig,
+        ) -> Result<RpcSimulateTransactionResult> {
+            self.simulation
+                .clone()
+                .ok_or_else(|| method_not_found("simulateTransaction").into())
+        }
+
+        fn send_transaction(
+            &self,
+            transaction: &VersionedTransaction,
+            _config: RpcSendTransactionConfig,
+        ) -> Result<Signature> {
+            self.sent.borrow_mut().push(transaction.signatures[0]);
+            Ok(transaction.signatures[0])
+        }
+    }
+}